@@ -1,8 +1,9 @@
-use hyper::{Body, Request, Response, Server};
+use hyper::{Body, Request, Response};
 // Import the routerify prelude traits.
 use routerify::prelude::*;
-use routerify::{Middleware, Router, RouterService};
+use routerify::{Middleware, Router};
 use std::{convert::Infallible, net::SocketAddr};
+use tokio::net::TcpListener;
 
 // A handler for "/" page.
 async fn home_handler(_: Request<Body>) -> Result<Response<Body>, Infallible> {
@@ -36,17 +37,13 @@ fn router() -> Router<Body, Infallible> {
 async fn main() {
     let router = router();
 
-    // Create a Service from the router above to handle incoming requests.
-    let service = RouterService::new(router).unwrap();
-
     // The address on which the server will be listening.
     let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
+    let listener = TcpListener::bind(addr).await.unwrap();
 
-    // Create a server by passing the created service to `.serve` method.
-    let server = Server::bind(&addr).serve(service);
-
+    // Serve the router until a Ctrl-C or a SIGTERM signal is received, then shut down gracefully.
     println!("App is running on: {}", addr);
-    if let Err(err) = server.await {
+    if let Err(err) = routerify::serve(listener, router).await {
         eprintln!("Server error: {}", err);
     }
 }
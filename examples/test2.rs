@@ -43,7 +43,7 @@ async fn error_handler(err: routerify::RouteError, req_info: RequestInfo) -> Res
 fn router2() -> Router<Body, routerify::Error> {
     Router::builder()
         .data(111_u32)
-        .get("/a", |req| async move {
+        .get("/a", |req: Request<Body>| async move {
             println!("Router2 Data: {:?}", req.data::<&str>());
             println!("Router2 Data: {:?}", req.data::<State>().map(|s| s.0));
             println!("Router2 Data: {:?}", req.data::<u32>());
@@ -56,7 +56,7 @@ fn router2() -> Router<Body, routerify::Error> {
 fn router3() -> Router<Body, routerify::Error> {
     Router::builder()
         .data(555_u32)
-        .get("/h/g/j", |req| async move {
+        .get("/h/g/j", |req: Request<Body>| async move {
             println!("Router3 Data: {:?}", req.data::<&str>());
             println!("Router3 Data: {:?}", req.data::<State>().map(|s| s.0));
             println!("Router3 Data: {:?}", req.data::<u32>());
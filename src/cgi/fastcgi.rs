@@ -0,0 +1,381 @@
+use super::CgiHandler;
+use crate::{Error, RouteError};
+use hyper::body::{Buf, HttpBody};
+use hyper::{Body, Response, StatusCode};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+const MAX_CONTENT_LEN: usize = u16::MAX as usize;
+// The params hold the request line and the headers, the larger ones are rejected like hyper does.
+const MAX_PARAMS_LEN: usize = 256 * 1024;
+
+const BEGIN_REQUEST: u8 = 1;
+const ABORT_REQUEST: u8 = 2;
+const END_REQUEST: u8 = 3;
+const PARAMS: u8 = 4;
+const STDIN: u8 = 5;
+const STDOUT: u8 = 6;
+const DATA: u8 = 8;
+const GET_VALUES: u8 = 9;
+const GET_VALUES_RESULT: u8 = 10;
+const UNKNOWN_TYPE: u8 = 11;
+
+const RESPONDER: u16 = 1;
+const KEEP_CONN: u8 = 1;
+
+const REQUEST_COMPLETE: u8 = 0;
+const CANT_MPX_CONN: u8 = 1;
+const UNKNOWN_ROLE: u8 = 3;
+
+struct Record {
+    kind: u8,
+    request_id: u16,
+    content: Vec<u8>,
+}
+
+// The request which is being received, it's dispatched once its params and its stdin are complete.
+struct PendingRequest {
+    id: u16,
+    keep_conn: bool,
+    // It's dropped once the params exceed the `MAX_PARAMS_LEN`.
+    params: Option<Vec<u8>>,
+    params_done: bool,
+    // It's dropped once the body exceeds the body limit.
+    stdin: Option<Vec<u8>>,
+    stdin_done: bool,
+}
+
+pub(super) async fn serve_connection<B, E, IO>(handler: &CgiHandler<B, E>, io: IO) -> crate::Result<()>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B::Data: Send,
+    B::Error: Into<RouteError>,
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, writer) = tokio::io::split(io);
+    let mut reader = BufReader::new(reader);
+    let mut writer = BufWriter::new(writer);
+
+    let mut pending: Option<PendingRequest> = None;
+
+    while let Some(record) = read_record(&mut reader).await? {
+        match record.kind {
+            // The management records aren't bound to a request.
+            GET_VALUES if record.request_id == 0 => {
+                let mut result = Vec::new();
+                for (name, _) in parse_params(&record.content)? {
+                    match name.as_str() {
+                        "FCGI_MAX_REQS" => encode_param(&mut result, &name, "1"),
+                        "FCGI_MPXS_CONNS" => encode_param(&mut result, &name, "0"),
+                        _ => {}
+                    }
+                }
+                write_record(&mut writer, GET_VALUES_RESULT, 0, &result).await?;
+                writer.flush().await?;
+            }
+            BEGIN_REQUEST => {
+                let content = &record.content;
+                if content.len() < 3 {
+                    return Err(invalid_data("Invalid FastCGI begin request record").into());
+                }
+
+                let role = u16::from_be_bytes([content[0], content[1]]);
+                let keep_conn = content[2] & KEEP_CONN != 0;
+
+                if pending.is_some() {
+                    write_end_request(&mut writer, record.request_id, CANT_MPX_CONN).await?;
+                } else if role != RESPONDER {
+                    write_end_request(&mut writer, record.request_id, UNKNOWN_ROLE).await?;
+                    if !keep_conn {
+                        return Ok(());
+                    }
+                } else {
+                    pending = Some(PendingRequest {
+                        id: record.request_id,
+                        keep_conn,
+                        params: Some(Vec::new()),
+                        params_done: false,
+                        stdin: Some(Vec::new()),
+                        stdin_done: false,
+                    });
+                }
+            }
+            ABORT_REQUEST | PARAMS | STDIN | DATA => {
+                let req = match pending.as_mut() {
+                    Some(req) if req.id == record.request_id => req,
+                    // The records of the rejected or the finished requests are ignored.
+                    _ => continue,
+                };
+
+                match record.kind {
+                    ABORT_REQUEST => {
+                        let keep_conn = req.keep_conn;
+                        pending = None;
+                        write_end_request(&mut writer, record.request_id, REQUEST_COMPLETE).await?;
+                        if !keep_conn {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    PARAMS if record.content.is_empty() => req.params_done = true,
+                    PARAMS => req.params = append(req.params.take(), &record.content, MAX_PARAMS_LEN),
+                    STDIN if record.content.is_empty() => req.stdin_done = true,
+                    STDIN => req.stdin = append(req.stdin.take(), &record.content, handler.body_limit.0),
+                    // The data stream is only sent to the filters.
+                    _ => {}
+                }
+
+                if req.params_done && req.stdin_done {
+                    let req = pending.take().expect("The pending FastCGI request is missing");
+                    respond(handler, &mut writer, req.id, req.params, req.stdin).await?;
+                    if !req.keep_conn {
+                        return Ok(());
+                    }
+                }
+            }
+            kind => {
+                let mut content = [0; 8];
+                content[0] = kind;
+                write_record(&mut writer, UNKNOWN_TYPE, 0, &content).await?;
+                writer.flush().await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn respond<B, E, W>(
+    handler: &CgiHandler<B, E>,
+    writer: &mut W,
+    request_id: u16,
+    params: Option<Vec<u8>>,
+    stdin: Option<Vec<u8>>,
+) -> crate::Result<()>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B::Data: Send,
+    B::Error: Into<RouteError>,
+    W: AsyncWrite + Unpin,
+{
+    let params = match params {
+        Some(params) => params,
+        None => {
+            let res = super::error_response(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+            return write_response(writer, request_id, res).await;
+        }
+    };
+
+    let stdin = match stdin {
+        Some(stdin) => stdin,
+        None => return write_response(writer, request_id, super::error_response(StatusCode::PAYLOAD_TOO_LARGE)).await,
+    };
+
+    let req = match parse_params(&params).and_then(|params| super::into_request(params, Body::from(stdin))) {
+        Ok(req) => req,
+        Err(err) => {
+            log::debug!("Couldn't convert the FastCGI request: {}", err);
+            return write_response(writer, request_id, super::error_response(StatusCode::BAD_REQUEST)).await;
+        }
+    };
+
+    match handler.dispatch(req).await {
+        Ok(res) => write_response(writer, request_id, res).await,
+        Err(err) => {
+            log::error!("Couldn't process the FastCGI request: {}", err);
+            write_response(
+                writer,
+                request_id,
+                super::error_response(StatusCode::INTERNAL_SERVER_ERROR),
+            )
+            .await
+        }
+    }
+}
+
+// Appends the content of a stream record, the stream is dropped once it exceeds the limit.
+fn append(stream: Option<Vec<u8>>, content: &[u8], limit: usize) -> Option<Vec<u8>> {
+    stream
+        .filter(|stream| stream.len() + content.len() <= limit)
+        .map(|mut stream| {
+            stream.extend_from_slice(content);
+            stream
+        })
+}
+
+// Writes the response to the stdout stream and ends the request.
+async fn write_response<B, W>(writer: &mut W, request_id: u16, res: Response<B>) -> crate::Result<()>
+where
+    B: HttpBody,
+    B::Error: Into<RouteError>,
+    W: AsyncWrite + Unpin,
+{
+    let (parts, body) = res.into_parts();
+    write_stream(writer, STDOUT, request_id, &super::response_head(&parts)).await?;
+
+    tokio::pin!(body);
+    // The error of the body isn't required to be `Send`, so it's not kept across the awaits.
+    loop {
+        let mut chunk = match body.data().await {
+            Some(chunk) => chunk.map_err(Into::into)?,
+            None => break,
+        };
+        write_stream(writer, STDOUT, request_id, &chunk.copy_to_bytes(chunk.remaining())).await?;
+    }
+
+    // An empty record closes the stream.
+    write_record(writer, STDOUT, request_id, &[]).await?;
+    write_end_request(writer, request_id, REQUEST_COMPLETE).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+async fn read_record<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Record>> {
+    let mut header = [0; HEADER_LEN];
+
+    // The web server closed the connection.
+    if reader.read(&mut header[..1]).await? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut header[1..]).await?;
+
+    if header[0] != VERSION {
+        return Err(invalid_data(format!("Unsupported FastCGI version: {}", header[0])));
+    }
+
+    let content_len = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let padding_len = header[6] as usize;
+
+    let mut content = vec![0; content_len + padding_len];
+    reader.read_exact(&mut content).await?;
+    content.truncate(content_len);
+
+    Ok(Some(Record {
+        kind: header[1],
+        request_id: u16::from_be_bytes([header[2], header[3]]),
+        content,
+    }))
+}
+
+async fn write_record<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    kind: u8,
+    request_id: u16,
+    content: &[u8],
+) -> io::Result<()> {
+    // The records are padded to a multiple of 8 bytes.
+    let padding_len = (8 - content.len() % 8) % 8;
+
+    let mut header = [VERSION, kind, 0, 0, 0, 0, padding_len as u8, 0];
+    header[2..4].copy_from_slice(&request_id.to_be_bytes());
+    header[4..6].copy_from_slice(&(content.len() as u16).to_be_bytes());
+
+    writer.write_all(&header).await?;
+    writer.write_all(content).await?;
+    writer.write_all(&[0; 8][..padding_len]).await
+}
+
+// Splits the data into as many records as needed, without closing the stream.
+async fn write_stream<W: AsyncWrite + Unpin>(writer: &mut W, kind: u8, request_id: u16, data: &[u8]) -> io::Result<()> {
+    for chunk in data.chunks(MAX_CONTENT_LEN) {
+        write_record(writer, kind, request_id, chunk).await?;
+    }
+    Ok(())
+}
+
+async fn write_end_request<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    request_id: u16,
+    protocol_status: u8,
+) -> io::Result<()> {
+    let content = [0, 0, 0, 0, protocol_status, 0, 0, 0];
+    write_record(writer, END_REQUEST, request_id, &content).await?;
+    writer.flush().await
+}
+
+// Parses the name-value pairs of the params and the get values records.
+fn parse_params(mut buf: &[u8]) -> crate::Result<Vec<(String, String)>> {
+    let mut params = Vec::new();
+
+    while !buf.is_empty() {
+        let name_len = read_len(&mut buf)?;
+        let val_len = read_len(&mut buf)?;
+
+        if buf.len() < name_len + val_len {
+            return Err(Error::new("Invalid FastCGI name-value pair").into());
+        }
+
+        let name = String::from_utf8_lossy(&buf[..name_len]).into_owned();
+        let val = String::from_utf8_lossy(&buf[name_len..name_len + val_len]).into_owned();
+        params.push((name, val));
+
+        buf = &buf[name_len + val_len..];
+    }
+
+    Ok(params)
+}
+
+// The lengths up to 127 are encoded in one byte, the longer ones in four bytes with the high bit set.
+fn read_len(buf: &mut &[u8]) -> crate::Result<usize> {
+    match buf.first() {
+        Some(len) if len >> 7 == 0 => {
+            *buf = &buf[1..];
+            Ok(*len as usize)
+        }
+        Some(_) if buf.len() >= 4 => {
+            let len = u32::from_be_bytes([buf[0] & 0x7f, buf[1], buf[2], buf[3]]);
+            *buf = &buf[4..];
+            Ok(len as usize)
+        }
+        _ => Err(Error::new("Invalid FastCGI name-value pair length").into()),
+    }
+}
+
+fn encode_param(buf: &mut Vec<u8>, name: &str, val: &str) {
+    for len in [name.len(), val.len()].iter() {
+        if *len < 128 {
+            buf.push(*len as u8);
+        } else {
+            buf.extend_from_slice(&(*len as u32 | 0x8000_0000).to_be_bytes());
+        }
+    }
+    buf.extend_from_slice(name.as_bytes());
+    buf.extend_from_slice(val.as_bytes());
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let long_val = "a".repeat(300);
+
+        let mut buf = Vec::new();
+        encode_param(&mut buf, "REQUEST_METHOD", "GET");
+        encode_param(&mut buf, "QUERY_STRING", "");
+        encode_param(&mut buf, "HTTP_X_LONG", &long_val);
+
+        let params = parse_params(&buf).unwrap();
+        assert_eq!(
+            params,
+            vec![
+                ("REQUEST_METHOD".to_owned(), "GET".to_owned()),
+                ("QUERY_STRING".to_owned(), "".to_owned()),
+                ("HTTP_X_LONG".to_owned(), long_val),
+            ]
+        );
+
+        assert!(parse_params(&buf[..buf.len() - 1]).is_err());
+        assert!(parse_params(&[0x80, 0]).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! Adapters to serve a router as a [CGI](https://www.rfc-editor.org/rfc/rfc3875) script or as a
+//! [FastCGI](https://fastcgi-archives.github.io/FastCGI_Specification.html) application.
+//!
+//! This module is available with the `cgi` feature. The requests are built from the CGI meta-variables: the method
+//! from `REQUEST_METHOD`, the path from `PATH_INFO`, the query from `QUERY_STRING` and the headers from the `HTTP_*`,
+//! `CONTENT_TYPE` and `CONTENT_LENGTH` variables. The remote address is taken from `REMOTE_ADDR` and `REMOTE_PORT`,
+//! and the local address from `SERVER_ADDR` and `SERVER_PORT`.
+//!
+//! The responses are written in the CGI format i.e. a `Status` header followed by the response headers and the body.
+//!
+//! The request bodies are limited to the [`BodyLimit`](../extract/struct.BodyLimit.html) of the handler, the requests
+//! with a larger body get `413 Payload Too Large` without reaching the router.
+//!
+//! # Examples
+//!
+//! Running the router as a CGI script:
+//!
+//! ```no_run
+//! use hyper::{Body, Response};
+//! use routerify::cgi::CgiHandler;
+//! use routerify::Router;
+//! use std::convert::Infallible;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router: Router<Body, Infallible> = Router::builder()
+//!         .get("/", || async move { Ok(Response::new(Body::from("Hello, world!"))) })
+//!         .build()
+//!         .unwrap();
+//!
+//!     CgiHandler::new(router).unwrap().run_cgi().await.unwrap();
+//! }
+//! ```
+//!
+//! Serving the router as a FastCGI application:
+//!
+//! ```no_run
+//! use hyper::{Body, Response};
+//! use routerify::cgi::CgiHandler;
+//! use routerify::Router;
+//! use std::convert::Infallible;
+//! use tokio::net::TcpListener;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router: Router<Body, Infallible> = Router::builder()
+//!         .get("/", || async move { Ok(Response::new(Body::from("Hello, world!"))) })
+//!         .build()
+//!         .unwrap();
+//!
+//!     let listener = TcpListener::bind("127.0.0.1:9000").await.unwrap();
+//!     CgiHandler::new(router).unwrap().serve_fastcgi(listener).await.unwrap();
+//! }
+//! ```
+
+use crate::extract::BodyLimit;
+use crate::helpers;
+use crate::server::{self, Listener};
+use crate::service::{InFlightBody, RequestServiceBuilder};
+use crate::types::{ConnectionInfo, PeerAddr};
+use crate::{Error, RouteError, Router};
+use hyper::body::{Buf, HttpBody};
+use hyper::header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::Service;
+use hyper::{Body, Request, Response, StatusCode, Version};
+use percent_encoding::{utf8_percent_encode, AsciiSet};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+mod fastcgi;
+
+// The `PATH_INFO` variable is already decoded, so the `%` has to be encoded too.
+const PATH_INFO: &AsciiSet = &helpers::PATH_ENCODE_SET.add(b'%');
+
+/// Dispatches the CGI and the FastCGI requests through a router.
+///
+/// Please refer to the [module](./index.html) documentation for examples.
+pub struct CgiHandler<B, E> {
+    builder: RequestServiceBuilder<B, E>,
+    body_limit: BodyLimit,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    CgiHandler<B, E>
+{
+    /// Creates a new handler for the router.
+    pub fn new(router: Router<B, E>) -> crate::Result<CgiHandler<B, E>> {
+        Ok(CgiHandler {
+            builder: RequestServiceBuilder::new(router)?,
+            body_limit: BodyLimit::default(),
+        })
+    }
+
+    /// Sets the maximum size of the request bodies, it's 2 MiB by default.
+    pub fn body_limit(mut self, limit: BodyLimit) -> Self {
+        self.body_limit = limit;
+        self
+    }
+}
+
+impl<B, E> CgiHandler<B, E>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B::Data: Send,
+    B::Error: Into<RouteError>,
+{
+    /// Handles a single CGI request with the environment variables of the process, the stdin and the stdout.
+    pub async fn run_cgi(&self) -> crate::Result<()> {
+        let vars = std::env::vars_os()
+            .map(|(key, val)| (key.to_string_lossy().into_owned(), val.to_string_lossy().into_owned()));
+
+        self.handle_cgi(vars, tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Handles a single CGI request with the provided meta-variables, reading the body from the `stdin` and writing the
+    /// response to the `stdout`.
+    ///
+    /// The body is read up to the `CONTENT_LENGTH` bytes, as the web server isn't required to close the `stdin`. The
+    /// requests which can't be converted get `400 Bad Request` and the ones the router fails to process get
+    /// `500 Internal Server Error`.
+    pub async fn handle_cgi<I, K, V, R, W>(&self, vars: I, stdin: R, mut stdout: W) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let vars = vars
+            .into_iter()
+            .map(|(key, val)| (key.into(), val.into()))
+            .collect::<HashMap<String, String>>();
+
+        let content_length = match vars.get("CONTENT_LENGTH").map(|val| val.trim()) {
+            Some(val) if !val.is_empty() => match val.parse::<u64>() {
+                Ok(content_length) => content_length,
+                Err(err) => {
+                    log::debug!("Invalid CONTENT_LENGTH variable: {}", err);
+                    return write_response(error_response(StatusCode::BAD_REQUEST), &mut stdout).await;
+                }
+            },
+            _ => 0,
+        };
+        if content_length > self.body_limit.0 as u64 {
+            return write_response(error_response(StatusCode::PAYLOAD_TOO_LARGE), &mut stdout).await;
+        }
+
+        let mut body = Vec::new();
+        stdin.take(content_length).read_to_end(&mut body).await?;
+
+        let req = match into_request(vars, Body::from(body)) {
+            Ok(req) => req,
+            Err(err) => {
+                log::debug!("Couldn't convert the CGI request: {}", err);
+                return write_response(error_response(StatusCode::BAD_REQUEST), &mut stdout).await;
+            }
+        };
+
+        match self.dispatch(req).await {
+            Ok(res) => write_response(res, &mut stdout).await,
+            Err(err) => {
+                log::error!("Couldn't process the CGI request: {}", err);
+                write_response(error_response(StatusCode::INTERNAL_SERVER_ERROR), &mut stdout).await
+            }
+        }
+    }
+
+    /// Serves the FastCGI requests on the listener until the router's [shutdown](../struct.Shutdown.html) is triggered.
+    ///
+    /// Each connection is served in its own task, see the
+    /// [`serve_fastcgi_connection`](#method.serve_fastcgi_connection) method.
+    pub async fn serve_fastcgi<L: Listener>(&self, listener: L) -> crate::Result<()> {
+        let shutdown = self.builder.shutdown();
+        let mut listener = server::Accept::new(listener);
+
+        loop {
+            let io = tokio::select! {
+                io = std::future::poll_fn(|cx| listener.poll_accept(cx)) => io,
+                _ = shutdown.triggered() => return Ok(()),
+            };
+
+            let handler = CgiHandler {
+                builder: self.builder.clone(),
+                body_limit: self.body_limit,
+            };
+
+            tokio::spawn(async move {
+                if let Err(err) = handler.serve_fastcgi_connection(io).await {
+                    log::debug!("Couldn't serve a FastCGI connection: {}", err);
+                }
+            });
+        }
+    }
+
+    /// Serves the FastCGI requests sent over a connection, which is usually opened by the web server.
+    ///
+    /// The requests are served one at a time in the responder role. The connection is served until it's closed by the
+    /// web server, or after a request if the web server doesn't ask to keep the connection open.
+    ///
+    /// The requests which can't be converted get `400 Bad Request`, the ones with the params larger than 256 KiB get
+    /// `431 Request Header Fields Too Large` and the ones the router fails to process get `500 Internal Server Error`,
+    /// the connection is kept open for the next requests.
+    pub async fn serve_fastcgi_connection<IO>(&self, io: IO) -> crate::Result<()>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        fastcgi::serve_connection(self, io).await
+    }
+
+    async fn dispatch(&self, mut req: Request<Body>) -> crate::Result<Response<InFlightBody<B>>> {
+        let connection_info = req
+            .extensions_mut()
+            .remove::<ConnectionInfo>()
+            .unwrap_or_else(|| ConnectionInfo::new(PeerAddr::Unknown));
+
+        let mut service = self.builder.build(connection_info);
+        service.call(req).await
+    }
+}
+
+impl<B, E> Debug for CgiHandler<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "CgiHandler")
+    }
+}
+
+/// Converts the CGI meta-variables and the body into a request.
+///
+/// The [connection info](../struct.ConnectionInfo.html) with the remote and the local address is added as a request
+/// extension.
+pub fn into_request<I, K, V>(vars: I, body: Body) -> crate::Result<Request<Body>>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    let vars = vars
+        .into_iter()
+        .map(|(key, val)| (key.into(), val.into()))
+        .collect::<HashMap<String, String>>();
+
+    let method = vars
+        .get("REQUEST_METHOD")
+        .ok_or_else(|| Error::new("The CGI request doesn't have the `REQUEST_METHOD` variable"))?;
+
+    let path = match vars.get("PATH_INFO") {
+        Some(path) if !path.is_empty() => path.as_str(),
+        _ => "/",
+    };
+
+    let mut uri = utf8_percent_encode(path, PATH_INFO).to_string();
+    if let Some(query) = vars.get("QUERY_STRING").filter(|query| !query.is_empty()) {
+        uri.push('?');
+        uri.push_str(query);
+    }
+
+    let version = match vars.get("SERVER_PROTOCOL").map(String::as_str) {
+        Some("HTTP/0.9") => Version::HTTP_09,
+        Some("HTTP/1.0") => Version::HTTP_10,
+        Some("HTTP/2") | Some("HTTP/2.0") => Version::HTTP_2,
+        _ => Version::HTTP_11,
+    };
+
+    let mut builder = Request::builder().method(method.as_str()).uri(uri).version(version);
+    let headers = builder
+        .headers_mut()
+        .ok_or_else(|| Error::new("Couldn't create a request from the CGI variables"))?;
+
+    for (key, val) in vars.iter() {
+        let name = match key.as_str() {
+            "CONTENT_TYPE" => CONTENT_TYPE,
+            "CONTENT_LENGTH" => CONTENT_LENGTH,
+            _ => match key.strip_prefix("HTTP_") {
+                Some(name) => HeaderName::from_bytes(name.replace('_', "-").as_bytes())?,
+                None => continue,
+            },
+        };
+
+        if !val.is_empty() {
+            headers.insert(name, HeaderValue::from_str(val)?);
+        }
+    }
+
+    let mut connection_info = ConnectionInfo::new(socket_addr(&vars, "REMOTE_ADDR", "REMOTE_PORT"));
+    if let addr @ PeerAddr::Tcp(_) = socket_addr(&vars, "SERVER_ADDR", "SERVER_PORT") {
+        connection_info = connection_info.with_local_addr(addr);
+    }
+
+    let mut req = builder.body(body)?;
+    req.extensions_mut().insert(connection_info);
+
+    Ok(req)
+}
+
+/// Writes the response in the CGI format.
+///
+/// The status is written as the `Status` header, followed by the response headers, an empty line and the body.
+pub async fn write_response<B, W>(res: Response<B>, mut writer: W) -> crate::Result<()>
+where
+    B: HttpBody,
+    B::Error: Into<RouteError>,
+    W: AsyncWrite + Unpin,
+{
+    let (parts, body) = res.into_parts();
+    writer.write_all(&response_head(&parts)).await?;
+
+    tokio::pin!(body);
+    loop {
+        let mut chunk = match body.data().await {
+            Some(chunk) => chunk.map_err(Into::into)?,
+            None => break,
+        };
+        writer.write_all(&chunk.copy_to_bytes(chunk.remaining())).await?;
+    }
+
+    writer.flush().await?;
+
+    Ok(())
+}
+
+fn response_head(parts: &http::response::Parts) -> Vec<u8> {
+    let mut head = format!(
+        "Status: {} {}\r\n",
+        parts.status.as_u16(),
+        parts.status.canonical_reason().unwrap_or_default()
+    )
+    .into_bytes();
+
+    for (name, val) in parts.headers.iter() {
+        head.extend_from_slice(name.as_str().as_bytes());
+        head.extend_from_slice(b": ");
+        head.extend_from_slice(val.as_bytes());
+        head.extend_from_slice(b"\r\n");
+    }
+
+    head.extend_from_slice(b"\r\n");
+    head
+}
+
+// The response for the requests which don't reach the router or which the router fails to process.
+fn error_response(status: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *res.status_mut() = status;
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    res
+}
+
+// The port defaults to 0 as it's optional for the web servers.
+fn socket_addr(vars: &HashMap<String, String>, addr_key: &str, port_key: &str) -> PeerAddr {
+    let port = vars
+        .get(port_key)
+        .and_then(|port| port.parse::<u16>().ok())
+        .unwrap_or(0);
+
+    vars.get(addr_key)
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .map(|ip| PeerAddr::Tcp(SocketAddr::new(ip, port)))
+        .unwrap_or(PeerAddr::Unknown)
+}
@@ -0,0 +1,520 @@
+//! Response compression.
+//!
+//! This module is available with the `compression-gzip`, `compression-deflate` and `compression-br` features, or with
+//! the `compression` feature which enables all of them. The [`Compression`](./struct.Compression.html) post middleware
+//! compresses the response bodies with the best encoding that the client accepts via the `Accept-Encoding` header.
+//!
+//! The bodies are compressed chunk by chunk as they're streamed, nothing is buffered apart from the state of the
+//! encoder. The compressed output is flushed whenever the response body has no data ready, so the streaming responses
+//! like the Server-Sent Events keep reaching the client in time.
+//!
+//! The responses which are smaller than the minimum size, which already have a `Content-Encoding`, which have an
+//! already compressed content type e.g. `image/png` or `application/zip`, or which carry `Cache-Control: no-transform`
+//! are sent as they are.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::{Body, Response};
+//! use routerify::compression::Compression;
+//! use routerify::{RouteError, Router};
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .get("/", || async { Ok(Response::new(Body::from("Hello, World! ".repeat(100)))) })
+//!     .middleware(Compression::new().min_size(256).middleware())
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::types::RequestInfo;
+use crate::{Middleware, RouteError};
+use futures_core::Stream;
+use hyper::body::{Buf, Bytes, HttpBody, SizeHint};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Body, Method, Response, StatusCode};
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// The brotli settings, a moderate quality as the responses are compressed on the fly.
+#[cfg(feature = "compression-br")]
+const BROTLI_QUALITY: u32 = 4;
+#[cfg(feature = "compression-br")]
+const BROTLI_WINDOW: u32 = 22;
+#[cfg(feature = "compression-br")]
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+// The content types which are compressed already, every `image/`, `audio/` and `video/` type other than SVG too.
+const COMPRESSED_CONTENT_TYPES: &[&str] = &[
+    "application/gzip",
+    "application/vnd.rar",
+    "application/x-7z-compressed",
+    "application/x-bzip2",
+    "application/x-gzip",
+    "application/x-rar-compressed",
+    "application/x-xz",
+    "application/zip",
+    "application/zstd",
+    "font/woff",
+    "font/woff2",
+];
+
+/// A post middleware which compresses the response bodies, please refer to the [module documentation](./index.html)
+/// for an example.
+///
+/// The `Vary: Accept-Encoding` header is added to every response which could be compressed, so the caches keep the
+/// encodings apart. A strong `ETag` of a compressed response is made weak as the bytes differ from the original.
+#[derive(Debug, Clone)]
+pub struct Compression {
+    min_size: u64,
+}
+
+impl Compression {
+    /// Creates the middleware with a minimum size of 1 KiB.
+    pub fn new() -> Compression {
+        Compression { min_size: 1024 }
+    }
+
+    /// Sets the minimum size of the bodies which are compressed, the size is known from the `Content-Length` header or
+    /// the size hint of the body. The bodies of an unknown size are always compressed.
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Creates the post middleware at the `/*` path.
+    ///
+    /// It needs a body type which can be created from a [`CompressionBody`](./struct.CompressionBody.html), it's
+    /// implemented for `hyper::Body`.
+    pub fn middleware<B, E, ReqBody>(self) -> Middleware<B, E, ReqBody>
+    where
+        B: HttpBody + From<CompressionBody<B>> + Send + Sync + 'static,
+        B::Error: Into<RouteError>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    {
+        Middleware::post_with_info(move |res, req_info| {
+            let res = self.compress(res, &req_info);
+            async move { Ok(res) }
+        })
+    }
+
+    /// Compresses the response for the request if it should be, it's used to compress the responses from a custom post
+    /// middleware.
+    pub fn compress<B>(&self, res: Response<B>, req_info: &RequestInfo) -> Response<B>
+    where
+        B: HttpBody + From<CompressionBody<B>> + Send + 'static,
+        B::Error: Into<RouteError>,
+    {
+        if !self.is_compressible(&res, req_info.method()) {
+            return res;
+        }
+
+        let (mut parts, body) = res.into_parts();
+        append_vary(&mut parts.headers);
+
+        let encoding = match Encoding::negotiate(req_info.headers()) {
+            Some(encoding) => encoding,
+            None => return Response::from_parts(parts, body),
+        };
+
+        let headers = &mut parts.headers;
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::ACCEPT_RANGES);
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding.name()));
+
+        let weak_etag = match headers.get(header::ETAG) {
+            Some(etag) if !etag.as_bytes().starts_with(b"W/") => {
+                let mut weak_etag = b"W/".to_vec();
+                weak_etag.extend_from_slice(etag.as_bytes());
+                HeaderValue::from_bytes(&weak_etag).ok()
+            }
+            _ => None,
+        };
+        if let Some(weak_etag) = weak_etag {
+            headers.insert(header::ETAG, weak_etag);
+        }
+
+        let body = CompressionBody {
+            inner: Box::pin(body),
+            encoder: Some(Encoder::new(encoding)),
+            is_dirty: false,
+        };
+
+        Response::from_parts(parts, B::from(body))
+    }
+
+    fn is_compressible<B: HttpBody>(&self, res: &Response<B>, method: &Method) -> bool {
+        let status = res.status();
+        if method == Method::HEAD
+            || status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::PARTIAL_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            return false;
+        }
+
+        let headers = res.headers();
+        if headers.contains_key(header::CONTENT_ENCODING) || has_token(headers, header::CACHE_CONTROL, "no-transform") {
+            return false;
+        }
+
+        let size = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse::<u64>().ok())
+            .or_else(|| res.body().size_hint().exact());
+        if matches!(size, Some(size) if size < self.min_size) {
+            return false;
+        }
+
+        match headers.get(header::CONTENT_TYPE).and_then(|val| val.to_str().ok()) {
+            Some(content_type) => !is_compressed_content_type(content_type),
+            None => true,
+        }
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::new()
+    }
+}
+
+fn is_compressed_content_type(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if mime == "image/svg+xml" {
+        return false;
+    }
+
+    mime.starts_with("image/")
+        || mime.starts_with("audio/")
+        || mime.starts_with("video/")
+        || COMPRESSED_CONTENT_TYPES.contains(&mime.as_str())
+}
+
+fn append_vary(headers: &mut HeaderMap) {
+    if !has_token(headers, header::VARY, "accept-encoding") && !has_token(headers, header::VARY, "*") {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+// Checks if a comma separated header contains the token, ignoring the case.
+fn has_token(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .any(|val| val.trim().eq_ignore_ascii_case(token))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    #[cfg(feature = "compression-br")]
+    Brotli,
+    #[cfg(feature = "compression-gzip")]
+    Gzip,
+    #[cfg(feature = "compression-deflate")]
+    Deflate,
+}
+
+impl Encoding {
+    // In the order of preference when the qualities are equal.
+    const ALL: &'static [Encoding] = &[
+        #[cfg(feature = "compression-br")]
+        Encoding::Brotli,
+        #[cfg(feature = "compression-gzip")]
+        Encoding::Gzip,
+        #[cfg(feature = "compression-deflate")]
+        Encoding::Deflate,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "compression-br")]
+            Encoding::Brotli => "br",
+            #[cfg(feature = "compression-gzip")]
+            Encoding::Gzip => "gzip",
+            #[cfg(feature = "compression-deflate")]
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    // Picks the enabled encoding with the highest quality in the `Accept-Encoding` header, the `*` stands for the
+    // encodings which aren't listed.
+    fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+        let accepted = headers
+            .get_all(header::ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let name = parts.next().unwrap_or_default().trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())?;
+
+                Some((name, quality))
+            })
+            .collect::<Vec<_>>();
+
+        let quality_of = |encoding: Encoding| {
+            accepted
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(encoding.name()))
+                .or_else(|| accepted.iter().find(|(name, _)| *name == "*"))
+                .map_or(0.0, |(_, quality)| *quality)
+        };
+
+        let mut best: Option<(Encoding, f32)> = None;
+        for &encoding in Encoding::ALL {
+            let quality = quality_of(encoding);
+            if quality > 0.0 && !matches!(best, Some((_, best_quality)) if best_quality >= quality) {
+                best = Some((encoding, quality));
+            }
+        }
+
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+enum Encoder {
+    #[cfg(feature = "compression-br")]
+    Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+    #[cfg(feature = "compression-gzip")]
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    #[cfg(feature = "compression-deflate")]
+    Deflate(flate2::write::ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Encoder {
+        match encoding {
+            #[cfg(feature = "compression-br")]
+            Encoding::Brotli => Encoder::Brotli(Box::new(brotli::CompressorWriter::new(
+                Vec::new(),
+                BROTLI_BUFFER_SIZE,
+                BROTLI_QUALITY,
+                BROTLI_WINDOW,
+            ))),
+            #[cfg(feature = "compression-gzip")]
+            Encoding::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "compression-deflate")]
+            Encoding::Deflate => Encoder::Deflate(flate2::write::ZlibEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+        }
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            #[cfg(feature = "compression-br")]
+            Encoder::Brotli(encoder) => encoder.as_mut(),
+            #[cfg(feature = "compression-gzip")]
+            Encoder::Gzip(encoder) => encoder,
+            #[cfg(feature = "compression-deflate")]
+            Encoder::Deflate(encoder) => encoder,
+        }
+    }
+
+    // Takes the compressed bytes which the encoder has written so far.
+    fn take_output(&mut self) -> Bytes {
+        let output = match self {
+            #[cfg(feature = "compression-br")]
+            Encoder::Brotli(encoder) => encoder.get_mut(),
+            #[cfg(feature = "compression-gzip")]
+            Encoder::Gzip(encoder) => encoder.get_mut(),
+            #[cfg(feature = "compression-deflate")]
+            Encoder::Deflate(encoder) => encoder.get_mut(),
+        };
+
+        Bytes::from(std::mem::take(output))
+    }
+
+    fn finish(self) -> io::Result<Bytes> {
+        let output = match self {
+            #[cfg(feature = "compression-br")]
+            Encoder::Brotli(encoder) => encoder.into_inner(),
+            #[cfg(feature = "compression-gzip")]
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "compression-deflate")]
+            Encoder::Deflate(encoder) => encoder.finish()?,
+        };
+
+        Ok(Bytes::from(output))
+    }
+}
+
+/// The body of a compressed response, it compresses the chunks of the original body as they're polled.
+///
+/// The [`Compression`](./struct.Compression.html) middleware converts it into the body type of the router, a custom
+/// body type has to implement `From<CompressionBody<B>>` to be compressed. It's both an `HttpBody` and a `Stream` of
+/// the compressed chunks.
+pub struct CompressionBody<B> {
+    inner: Pin<Box<B>>,
+    // It's `None` once the body is finished.
+    encoder: Option<Encoder>,
+    // Whether some data was written to the encoder since the last flush.
+    is_dirty: bool,
+}
+
+impl<B> Debug for CompressionBody<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressionBody")
+            .field("is_finished", &self.encoder.is_none())
+            .finish()
+    }
+}
+
+impl<B> HttpBody for CompressionBody<B>
+where
+    B: HttpBody,
+    B::Error: Into<RouteError>,
+{
+    type Data = Bytes;
+    type Error = RouteError;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = &mut *self;
+
+        loop {
+            let encoder = match this.encoder {
+                Some(ref mut encoder) => encoder,
+                None => return Poll::Ready(None),
+            };
+
+            match this.inner.as_mut().poll_data(cx) {
+                Poll::Ready(Some(Ok(mut data))) => {
+                    while data.has_remaining() {
+                        let chunk = data.chunk();
+                        encoder.writer().write_all(chunk)?;
+                        let len = chunk.len();
+                        data.advance(len);
+                    }
+                    this.is_dirty = true;
+
+                    let output = encoder.take_output();
+                    if !output.is_empty() {
+                        return Poll::Ready(Some(Ok(output)));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => {
+                    let encoder = this.encoder.take().expect("The encoder is checked above");
+                    return Poll::Ready(Some(encoder.finish().map_err(Into::into)));
+                }
+                Poll::Pending => {
+                    // Nothing else is ready, so the data written so far is flushed to the client.
+                    if this.is_dirty {
+                        this.is_dirty = false;
+                        encoder.writer().flush()?;
+
+                        let output = encoder.take_output();
+                        if !output.is_empty() {
+                            return Poll::Ready(Some(Ok(output)));
+                        }
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.inner.as_mut().poll_trailers(cx).map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.encoder.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+impl<B> Stream for CompressionBody<B>
+where
+    B: HttpBody,
+    B::Error: Into<RouteError>,
+{
+    type Item = Result<Bytes, RouteError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_data(cx)
+    }
+}
+
+impl<B> From<CompressionBody<B>> for Body
+where
+    B: HttpBody + Send + 'static,
+    B::Error: Into<RouteError>,
+{
+    fn from(body: CompressionBody<B>) -> Body {
+        Body::wrap_stream(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(
+        feature = "compression-gzip",
+        feature = "compression-deflate",
+        feature = "compression-br"
+    ))]
+    fn test_negotiate() {
+        let accept_encoding = |val: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static(val));
+            headers
+        };
+
+        assert_eq!(
+            Encoding::negotiate(&accept_encoding("gzip, deflate, br")),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            Encoding::negotiate(&accept_encoding("deflate, gzip")),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            Encoding::negotiate(&accept_encoding("br;q=0.5, deflate;q=0.8")),
+            Some(Encoding::Deflate)
+        );
+        assert_eq!(
+            Encoding::negotiate(&accept_encoding("*;q=0.1, br;q=0")),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(Encoding::negotiate(&accept_encoding("identity")), None);
+        assert_eq!(Encoding::negotiate(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_compressed_content_types() {
+        assert!(is_compressed_content_type("image/png"));
+        assert!(is_compressed_content_type("application/ZIP"));
+        assert!(!is_compressed_content_type("image/svg+xml"));
+        assert!(!is_compressed_content_type("text/html; charset=utf-8"));
+    }
+}
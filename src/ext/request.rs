@@ -1,9 +1,11 @@
 use crate::data_map::SharedDataMap;
-use crate::types::{RequestContext, RequestMeta, RouteParams};
+use crate::types::{
+    ClientInfo, ConnectionInfo, PeerAddr, QueryCache, QueryParams, RequestContext, RequestMeta, RouteParams, Shutdown,
+    UNKNOWN_CLIENT_INFO, UNKNOWN_CONNECTION_INFO,
+};
 use hyper::Request;
-use std::net::SocketAddr;
 
-/// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.14.4/hyper/struct.Request.html) and [`http::Parts`](https://docs.rs/http/0.2.4/http/request/struct.Parts.html) types with some helpful methods.
+/// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.14.4/hyper/struct.Request.html) with any body type and [`http::Parts`](https://docs.rs/http/0.2.4/http/request/struct.Parts.html) types with some helpful methods.
 pub trait RequestExt {
     /// It returns the route parameters as [RouteParams](../struct.RouteParams.html) type with the name of the parameter specified in the path as their respective keys.
     ///
@@ -12,12 +14,12 @@ pub trait RequestExt {
     /// ```
     /// use routerify::{Router, RouteParams};
     /// use routerify::ext::RequestExt;
-    /// use hyper::{Response, Body};
+    /// use hyper::{Request, Response, Body};
     /// # use std::convert::Infallible;
     ///
     /// # fn run() -> Router<Body, Infallible> {
     /// let router = Router::builder()
-    ///     .get("/users/:userName/books/:bookName", |req| async move {
+    ///     .get("/users/:userName/books/:bookName", |req: Request<Body>| async move {
     ///         let params: &RouteParams = req.params();
     ///         let user_name = params.get("userName").unwrap();
     ///         let book_name = params.get("bookName").unwrap();
@@ -39,12 +41,12 @@ pub trait RequestExt {
     /// ```
     /// use routerify::{Router, RouteParams};
     /// use routerify::ext::RequestExt;
-    /// use hyper::{Response, Body};
+    /// use hyper::{Request, Response, Body};
     /// # use std::convert::Infallible;
     ///
     /// # fn run() -> Router<Body, Infallible> {
     /// let router = Router::builder()
-    ///     .get("/users/:userName/books/:bookName", |req| async move {
+    ///     .get("/users/:userName/books/:bookName", |req: Request<Body>| async move {
     ///         let user_name = req.param("userName").unwrap();
     ///         let book_name = req.param("bookName").unwrap();
     ///
@@ -58,19 +60,78 @@ pub trait RequestExt {
     /// ```
     fn param<P: Into<String>>(&self, param_name: P) -> Option<&String>;
 
+    /// It returns the percent-decoded query string parameters as [QueryParams](../struct.QueryParams.html) type, they're
+    /// parsed on the first access and cached in the request extensions.
+    ///
+    /// The cache is renewed before the route handler is called if a pre middleware rewrote the query string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteError};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let router = Router::builder()
+    ///     .get("/books", |req: Request<Body>| async move {
+    ///         let page = req.query_params().parse::<u32>("page")?.unwrap_or(1);
+    ///
+    ///         Ok(Response::new(Body::from(format!("Page: {}", page))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn query_params(&self) -> &QueryParams;
+
+    /// It returns the first value of the query string parameter by its name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/search", |req: Request<Body>| async move {
+    ///         let text = req.query("q").unwrap_or_default();
+    ///         let tags = req.query_all("tag");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Searching {} in {:?}", text, tags))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn query(&self, name: &str) -> Option<&str>;
+
+    /// It returns all the values of the query string parameter by its name e.g. for `?tag=a&tag=b`.
+    fn query_all(&self, name: &str) -> Vec<&str>;
+
     /// It returns the remote address of the incoming request.
     ///
+    /// The address depends on the connection type, see [PeerAddr](../enum.PeerAddr.html). It's
+    /// [`PeerAddr::Unknown`](../enum.PeerAddr.html#variant.Unknown) if the request didn't come through a connection.
+    ///
     /// # Examples
     ///
     /// ```
     /// use routerify::{Router, RouteParams};
     /// use routerify::ext::RequestExt;
-    /// use hyper::{Response, Body};
+    /// use hyper::{Request, Response, Body};
     /// # use std::convert::Infallible;
     ///
     /// # fn run() -> Router<Body, Infallible> {
     /// let router = Router::builder()
-    ///     .get("/hello", |req| async move {
+    ///     .get("/hello", |req: Request<Body>| async move {
     ///         let remote_addr = req.remote_addr();
     ///
     ///         Ok(Response::new(Body::from(format!("Hello from : {}", remote_addr))))
@@ -81,7 +142,47 @@ pub trait RequestExt {
     /// # }
     /// # run();
     /// ```
-    fn remote_addr(&self) -> SocketAddr;
+    fn remote_addr(&self) -> PeerAddr;
+
+    /// It returns the information of the connection which the request came through e.g. the local address and the TLS
+    /// details. Please refer to the [ConnectionInfo](../struct.ConnectionInfo.html) for more info.
+    fn connection_info(&self) -> &ConnectionInfo;
+
+    /// It returns the client which sent the request as resolved through the trusted proxies. Please refer to the
+    /// [ClientInfo](../struct.ClientInfo.html) for more info.
+    fn client_info(&self) -> &ClientInfo;
+
+    /// It returns the [shutdown](../struct.Shutdown.html) handle of the router which processes the request, so a
+    /// long-running handler can observe the shutdown and finish early.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/long-poll", |req: Request<Body>| async move {
+    ///         let shutdown = req.shutdown().unwrap().clone();
+    ///
+    ///         let text = tokio::select! {
+    ///             _ = tokio::time::sleep(Duration::from_secs(30)) => "No new events",
+    ///             _ = shutdown.triggered() => "Shutting down",
+    ///         };
+    ///
+    ///         Ok(Response::new(Body::from(text)))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn shutdown(&self) -> Option<&Shutdown>;
 
     /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
     /// [`data`](../struct.RouterBuilder.html#method.data).
@@ -109,7 +210,7 @@ pub trait RequestExt {
     ///
     ///         Ok(req)
     ///     }))
-    ///     .get("/hello", |req| async move {
+    ///     .get("/hello", |req: Request<Body>| async move {
     ///         let text = req.context::<String>().unwrap();
     ///
     ///         Ok(Response::new(Body::from(format!("Hello from : {}", text))))
@@ -133,11 +234,31 @@ fn param<P: Into<String>>(ext: &http::Extensions, param_name: P) -> Option<&Stri
     params(ext).get(&param_name.into())
 }
 
-fn remote_addr(ext: &http::Extensions) -> SocketAddr {
+fn query_params(ext: &http::Extensions) -> &QueryParams {
+    ext.get::<QueryCache>()
+        .map(|cache| cache.params())
+        .expect("Routerify: No QueryCache added while processing request")
+}
+
+fn remote_addr(ext: &http::Extensions) -> PeerAddr {
+    connection_info(ext).peer_addr().clone()
+}
+
+fn client_info(ext: &http::Extensions) -> &ClientInfo {
     ext.get::<RequestMeta>()
-        .and_then(|meta| meta.remote_addr())
-        .copied()
-        .expect("Routerify: No remote address added while processing request")
+        .and_then(|meta| meta.client_info())
+        .unwrap_or(&UNKNOWN_CLIENT_INFO)
+}
+
+fn connection_info(ext: &http::Extensions) -> &ConnectionInfo {
+    ext.get::<RequestMeta>()
+        .and_then(|meta| meta.connection_info())
+        .map(|info| info.as_ref())
+        .unwrap_or(&UNKNOWN_CONNECTION_INFO)
+}
+
+fn shutdown(ext: &http::Extensions) -> Option<&Shutdown> {
+    ext.get::<Shutdown>()
 }
 
 fn data<T: Send + Sync + 'static>(ext: &http::Extensions) -> Option<&T> {
@@ -164,7 +285,7 @@ fn set_context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions, val: T)
     ctx.set(val)
 }
 
-impl RequestExt for Request<hyper::Body> {
+impl<ReqBody> RequestExt for Request<ReqBody> {
     fn params(&self) -> &RouteParams {
         params(self.extensions())
     }
@@ -173,10 +294,34 @@ impl RequestExt for Request<hyper::Body> {
         param(self.extensions(), param_name)
     }
 
-    fn remote_addr(&self) -> SocketAddr {
+    fn query_params(&self) -> &QueryParams {
+        query_params(self.extensions())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query_params().get(name)
+    }
+
+    fn query_all(&self, name: &str) -> Vec<&str> {
+        self.query_params().get_all(name)
+    }
+
+    fn remote_addr(&self) -> PeerAddr {
         remote_addr(self.extensions())
     }
 
+    fn connection_info(&self) -> &ConnectionInfo {
+        connection_info(self.extensions())
+    }
+
+    fn client_info(&self) -> &ClientInfo {
+        client_info(self.extensions())
+    }
+
+    fn shutdown(&self) -> Option<&Shutdown> {
+        shutdown(self.extensions())
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(self.extensions())
     }
@@ -199,10 +344,34 @@ impl RequestExt for http::request::Parts {
         param(&self.extensions, param_name)
     }
 
-    fn remote_addr(&self) -> SocketAddr {
+    fn query_params(&self) -> &QueryParams {
+        query_params(&self.extensions)
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query_params().get(name)
+    }
+
+    fn query_all(&self, name: &str) -> Vec<&str> {
+        self.query_params().get_all(name)
+    }
+
+    fn remote_addr(&self) -> PeerAddr {
         remote_addr(&self.extensions)
     }
 
+    fn connection_info(&self) -> &ConnectionInfo {
+        connection_info(&self.extensions)
+    }
+
+    fn client_info(&self) -> &ClientInfo {
+        client_info(&self.extensions)
+    }
+
+    fn shutdown(&self) -> Option<&Shutdown> {
+        shutdown(&self.extensions)
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(&self.extensions)
     }
@@ -0,0 +1,70 @@
+use super::{mime_type, read_body, ExtractFuture, FromRequest, Rejection};
+use crate::RouteError;
+use hyper::body::HttpBody;
+use hyper::{Request, StatusCode};
+use serde::de::DeserializeOwned;
+
+/// Extracts the `application/x-www-form-urlencoded` request body.
+///
+/// The request must have the `application/x-www-form-urlencoded` content type, otherwise it's rejected with
+/// `415 Unsupported Media Type`. It's rejected with `422 Unprocessable Entity` if the form doesn't match the type.
+///
+/// It's available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::Body;
+/// use routerify::extract::Form;
+/// use routerify::{RouteError, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Login {
+///     username: String,
+///     password: String,
+/// }
+///
+/// async fn login(Form(login): Form<Login>) -> Result<String, RouteError> {
+///     Ok(format!("Welcome {}", login.username))
+/// }
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .post("/login", login)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Form<T>(pub T);
+
+impl<T, ReqBody> FromRequest<ReqBody> for Form<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(async move {
+            if mime_type(req.headers()).as_deref() != Some("application/x-www-form-urlencoded") {
+                return Err(Rejection::new(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "Expected a request with the `application/x-www-form-urlencoded` content type",
+                ));
+            }
+
+            let body = read_body(req).await?;
+
+            serde_urlencoded::from_bytes(&body).map(Form).map_err(|e| {
+                Rejection::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Couldn't parse the form request body: {}", e),
+                )
+            })
+        })
+    }
+}
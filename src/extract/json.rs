@@ -0,0 +1,107 @@
+use super::{mime_type, read_body, ExtractFuture, FromRequest, Rejection};
+use crate::response::IntoResponse;
+use crate::RouteError;
+use hyper::body::HttpBody;
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::error::Category;
+
+/// Extracts the request body as JSON, or responds with a JSON body.
+///
+/// As an extractor, the request must have an `application/json` or an `application/*+json` content type, otherwise
+/// it's rejected with `415 Unsupported Media Type`. It's rejected with `400 Bad Request` if the body isn't valid JSON,
+/// and with `422 Unprocessable Entity` if the JSON doesn't match the type.
+///
+/// As a response, the value is serialized with the `application/json` content type.
+///
+/// It's available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::Body;
+/// use routerify::extract::Json;
+/// use routerify::{RouteError, Router};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize)]
+/// struct NewUser {
+///     name: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// async fn create_user(Json(user): Json<NewUser>) -> Result<Json<User>, RouteError> {
+///     Ok(Json(User { id: 1, name: user.name }))
+/// }
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .post("/users", create_user)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T, ReqBody> FromRequest<ReqBody> for Json<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(async move {
+            if !matches!(mime_type(req.headers()), Some(mime) if is_json(&mime)) {
+                return Err(Rejection::new(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "Expected a request with the `application/json` content type",
+                ));
+            }
+
+            let body = read_body(req).await?;
+
+            serde_json::from_slice(&body).map(Json).map_err(|e| {
+                let status = match e.classify() {
+                    Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+                    _ => StatusCode::BAD_REQUEST,
+                };
+                Rejection::new(status, format!("Couldn't parse the JSON request body: {}", e))
+            })
+        })
+    }
+}
+
+impl<B, T> IntoResponse<B> for Json<T>
+where
+    B: From<Vec<u8>> + From<&'static str>,
+    T: Serialize,
+{
+    fn into_response(self) -> Response<B> {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => {
+                let mut res = Response::new(B::from(body));
+                res.headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                res
+            }
+            Err(err) => {
+                log::error!("Couldn't serialize the JSON response body: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+fn is_json(mime: &str) -> bool {
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
@@ -0,0 +1,574 @@
+//! Typed extractors for the arguments of the route handlers.
+//!
+//! A handler can be an async function taking any number of extractors as its arguments, instead of a raw
+//! `Request<ReqBody>`, and it's registered as is with the [`RouterBuilder`](../struct.RouterBuilder.html) methods.
+//! All the arguments except the last one must implement the [`FromRequestParts`](./trait.FromRequestParts.html)
+//! trait, the last one can consume the whole request e.g. the body via the [`FromRequest`](./trait.FromRequest.html)
+//! trait. The handler can return any [`IntoResponse`](../trait.IntoResponse.html) type e.g. a string or a status code,
+//! instead of a `Response<B>`.
+//!
+//! The following extractors are available:
+//!
+//! * [`Path<T>`](./struct.Path.html) parses the route parameter of the route.
+//! * [`RouteParams`](../struct.RouteParams.html) contains all the route parameters.
+//! * [`QueryParams`](../struct.QueryParams.html) contains all the query string parameters.
+//! * [`Data<T>`](./struct.Data.html) clones the data shared via the [`data`](../struct.RouterBuilder.html#method.data)
+//!   method of the router or its scopes.
+//! * [`Context<T>`](./struct.Context.html) clones the data put into the request context.
+//! * [`PeerAddr`](../enum.PeerAddr.html), [`ConnectionInfo`](../struct.ConnectionInfo.html) and
+//!   [`ClientInfo`](../struct.ClientInfo.html) describe the client and the connection.
+//! * `HeaderMap`, `Method`, `Uri` and `Version` are cloned from the request.
+//! * `Request<ReqBody>` is the whole request, and `Bytes` and `String` are the whole body, up to the
+//!   [`BodyLimit`](./struct.BodyLimit.html).
+//! * With the `serde` feature, [`Json<T>`](./struct.Json.html) and [`Form<T>`](./struct.Form.html) deserialize the
+//!   body, and [`Query<T>`](./struct.Query.html) deserializes the query string.
+//! * With the `multipart` feature, [`Multipart`](./struct.Multipart.html) streams the fields of a
+//!   `multipart/form-data` body.
+//! * `Option<T>` and `Result<T, Rejection>` don't reject the request if the inner extractor fails.
+//!
+//! If an extractor fails, the handler isn't called and the [`Rejection`](./struct.Rejection.html) is passed to the
+//! error handler of the router as a [`RouteError`](../type.RouteError.html). The default error handler responds with
+//! the status of the rejection, along with its message for the client errors.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::{Body, Response};
+//! use routerify::extract::{Data, Path};
+//! use routerify::{PeerAddr, RouteError, Router};
+//!
+//! #[derive(Clone)]
+//! struct Greeting(&'static str);
+//!
+//! async fn get_user(
+//!     Path(id): Path<u64>,
+//!     Data(greeting): Data<Greeting>,
+//!     addr: PeerAddr,
+//! ) -> Result<Response<Body>, RouteError> {
+//!     Ok(Response::new(Body::from(format!("{} user {} from {}", greeting.0, id, addr))))
+//! }
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .data(Greeting("Hello"))
+//!     .get("/users/:id", get_user)
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::ext::RequestExt;
+use crate::response::IntoResponse;
+use crate::types::{ClientInfo, ConnectionInfo, ParamError, PeerAddr, QueryError, QueryParams, RouteParams};
+use crate::RouteError;
+use http::request::Parts;
+use hyper::body::{Buf, Bytes, HttpBody};
+use hyper::{header, HeaderMap, Method, Request, Response, StatusCode, Uri, Version};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+pub use self::form::Form;
+#[cfg(feature = "serde")]
+pub use self::json::Json;
+#[cfg(feature = "multipart")]
+pub use self::multipart::{Field, FieldData, Multipart, MultipartConfig, MultipartError, TempFile};
+#[cfg(feature = "serde")]
+pub use self::query::Query;
+
+#[cfg(feature = "serde")]
+mod form;
+#[cfg(feature = "serde")]
+mod json;
+#[cfg(feature = "multipart")]
+mod multipart;
+#[cfg(feature = "serde")]
+mod query;
+
+/// The future returned by a [`Handler`](./trait.Handler.html), the errors of the handler and the extractors are
+/// boxed into a [`RouteError`](../type.RouteError.html).
+pub type HandlerFuture<B> = Pin<Box<dyn Future<Output = Result<Response<B>, RouteError>> + Send + 'static>>;
+
+/// The future returned by a [`FromRequest`](./trait.FromRequest.html) extractor.
+pub type ExtractFuture<T> = Pin<Box<dyn Future<Output = Result<T, Rejection>> + Send + 'static>>;
+
+/// The error returned when an extractor fails, it carries the status of the response which should be sent.
+///
+/// The default error handler of the router responds with the status of the rejection, along with its message for the
+/// client errors. A custom error handler can downcast the [`RouteError`](../type.RouteError.html) to it.
+///
+/// The errors of the typed accessors e.g. [`ParamError`](../enum.ParamError.html),
+/// [`QueryError`](../struct.QueryError.html) or [`MultipartError`](./enum.MultipartError.html), are converted into a
+/// rejection via the `From` trait, and they are kept as its `source`. They should be converted where they're raised
+/// e.g. `req.params().parse::<u64>("id").map_err(Rejection::from)?`, otherwise the default error handler treats them
+/// as any other error.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Response};
+/// use routerify::extract::Rejection;
+/// use routerify::RouteError;
+///
+/// async fn error_handler(err: RouteError) -> Response<Body> {
+///     let (status, message) = match err.downcast_ref::<Rejection>() {
+///         Some(rejection) => (rejection.status(), rejection.message().to_owned()),
+///         None => (hyper::StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong".to_owned()),
+///     };
+///
+///     Response::builder().status(status).body(Body::from(message)).unwrap()
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Rejection {
+    status: StatusCode,
+    msg: String,
+    source: Option<Arc<dyn std::error::Error + Send + Sync + 'static>>,
+}
+
+impl Rejection {
+    /// Creates a new rejection with the status of the response and a message describing the failure.
+    pub fn new<M: Into<String>>(status: StatusCode, msg: M) -> Rejection {
+        Rejection {
+            status,
+            msg: msg.into(),
+            source: None,
+        }
+    }
+
+    // The original error is kept as the source, so it can still be inspected by a custom error handler.
+    pub(crate) fn from_error<T: std::error::Error + Send + Sync + 'static>(status: StatusCode, err: T) -> Rejection {
+        Rejection {
+            status,
+            msg: err.to_string(),
+            source: Some(Arc::new(err)),
+        }
+    }
+
+    /// Returns the status of the response which should be sent.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the message describing the failure.
+    pub fn message(&self) -> &str {
+        self.msg.as_str()
+    }
+}
+
+impl From<ParamError> for Rejection {
+    fn from(err: ParamError) -> Rejection {
+        Rejection::from_error(err.status(), err)
+    }
+}
+
+impl From<QueryError> for Rejection {
+    fn from(err: QueryError) -> Rejection {
+        Rejection::from_error(err.status(), err)
+    }
+}
+
+impl PartialEq for Rejection {
+    fn eq(&self, other: &Rejection) -> bool {
+        self.status == other.status && self.msg == other.msg
+    }
+}
+
+impl Eq for Rejection {}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "routerify::Rejection: {}: {}", self.status, self.msg)
+    }
+}
+
+impl Debug for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "routerify::Rejection: {}: {}", self.status, self.msg)
+    }
+}
+
+impl std::error::Error for Rejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|err| err as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// An extractor which only needs the request head.
+///
+/// It can be used for any argument of a handler.
+pub trait FromRequestParts: Sized {
+    /// Extracts the value from the request head.
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection>;
+}
+
+/// An extractor which consumes the whole request.
+///
+/// It can only be used for the last argument of a handler. Every [`FromRequestParts`](./trait.FromRequestParts.html)
+/// extractor is also a `FromRequest` extractor.
+pub trait FromRequest<ReqBody>: Sized {
+    /// Extracts the value from the request.
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self>;
+}
+
+impl<T: FromRequestParts + Send + 'static, ReqBody> FromRequest<ReqBody> for T {
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        let (parts, _) = req.into_parts();
+        let res = T::from_request_parts(&parts);
+        Box::pin(async move { res })
+    }
+}
+
+/// A function which can be used as a route handler.
+///
+/// It's implemented for the async functions and the closures taking up to eight extractors as their arguments, and
+/// returning a `Result` of any [`IntoResponse`](../trait.IntoResponse.html) type. The error type of the handler is the
+/// error type of the router.
+///
+/// The type of a closure argument can't be inferred from this trait, so it has to be annotated e.g.
+/// `|req: Request<Body>| async move { ... }`.
+pub trait Handler<T, B, E, ReqBody>: Send + Sync + 'static {
+    /// Extracts the arguments from the request and calls the handler with them.
+    fn call(self: Arc<Self>, req: Request<ReqBody>) -> HandlerFuture<B>;
+}
+
+/// Returns the [`Handler`](./trait.Handler.html) as is.
+///
+/// The [`RouterBuilder`](../struct.RouterBuilder.html) methods accept any handler directly, so it's only kept for the
+/// existing code.
+pub fn handler<H, T, B, E, ReqBody>(handler: H) -> impl Handler<T, B, E, ReqBody>
+where
+    H: Handler<T, B, E, ReqBody>,
+{
+    handler
+}
+
+impl<F, Fut, T, B, E, ReqBody> Handler<(), B, E, ReqBody> for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    T: IntoResponse<B>,
+    E: Into<RouteError>,
+{
+    fn call(self: Arc<Self>, _req: Request<ReqBody>) -> HandlerFuture<B> {
+        let fut = (self)();
+        Box::pin(async move { fut.await.map(IntoResponse::into_response).map_err(Into::into) })
+    }
+}
+
+macro_rules! impl_handler {
+    ($($ty:ident),*; $last:ident) => {
+        impl<F, Fut, T, B, E, ReqBody, $($ty,)* $last> Handler<($($ty,)* $last,), B, E, ReqBody> for F
+        where
+            F: Fn($($ty,)* $last) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<T, E>> + Send + 'static,
+            T: IntoResponse<B>,
+            E: Into<RouteError>,
+            ReqBody: Send + 'static,
+            $($ty: FromRequestParts + Send + 'static,)*
+            $last: FromRequest<ReqBody> + Send + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn call(self: Arc<Self>, req: Request<ReqBody>) -> HandlerFuture<B> {
+                Box::pin(async move {
+                    let (parts, body) = req.into_parts();
+                    $(let $ty = $ty::from_request_parts(&parts)?;)*
+                    let $last = $last::from_request(Request::from_parts(parts, body)).await?;
+
+                    (self)($($ty,)* $last)
+                        .await
+                        .map(IntoResponse::into_response)
+                        .map_err(Into::into)
+                })
+            }
+        }
+    };
+}
+
+impl_handler!(; T1);
+impl_handler!(T1; T2);
+impl_handler!(T1, T2; T3);
+impl_handler!(T1, T2, T3; T4);
+impl_handler!(T1, T2, T3, T4; T5);
+impl_handler!(T1, T2, T3, T4, T5; T6);
+impl_handler!(T1, T2, T3, T4, T5, T6; T7);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7; T8);
+
+/// Extracts the route parameter and parses it via the `FromStr` trait.
+///
+/// The route must have exactly one parameter, use the [`RouteParams`](../struct.RouteParams.html) extractor for the
+/// routes with more parameters. It's rejected with `400 Bad Request` if the parameter can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+impl<T> FromRequestParts for Path<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        let params = parts.params();
+
+        let mut names = params.params_names();
+        let name = match (names.next(), names.next()) {
+            (Some(name), None) => name,
+            _ => {
+                return Err(Rejection::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "The `Path` extractor requires one route parameter, found {}",
+                        params.len()
+                    ),
+                ))
+            }
+        };
+
+        params.parse(name).map(Path).map_err(Rejection::from)
+    }
+}
+
+/// Extracts a clone of the data shared via the [`data`](../struct.RouterBuilder.html#method.data) method of the router
+/// or its scopes.
+///
+/// It's rejected with `500 Internal Server Error` if no data of the type is shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Data<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequestParts for Data<T> {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        parts.data::<T>().cloned().map(Data).ok_or_else(|| {
+            Rejection::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(
+                    "No data of type `{}` is shared with the router",
+                    std::any::type_name::<T>()
+                ),
+            )
+        })
+    }
+}
+
+/// Extracts a clone of the data put into the request context via the
+/// [`set_context`](../ext/trait.RequestExt.html#method.set_context) method.
+///
+/// It's rejected with `500 Internal Server Error` if the context doesn't have any data of the type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Context<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequestParts for Context<T> {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        parts.context::<T>().map(Context).ok_or_else(|| {
+            Rejection::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(
+                    "No data of type `{}` is in the request context",
+                    std::any::type_name::<T>()
+                ),
+            )
+        })
+    }
+}
+
+impl FromRequestParts for RouteParams {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.params().clone())
+    }
+}
+
+impl FromRequestParts for QueryParams {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.query_params().clone())
+    }
+}
+
+impl FromRequestParts for PeerAddr {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.remote_addr())
+    }
+}
+
+impl FromRequestParts for ConnectionInfo {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.connection_info().clone())
+    }
+}
+
+impl FromRequestParts for ClientInfo {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.client_info().clone())
+    }
+}
+
+impl FromRequestParts for HeaderMap {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.headers.clone())
+    }
+}
+
+impl FromRequestParts for Method {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.method.clone())
+    }
+}
+
+impl FromRequestParts for Uri {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.uri.clone())
+    }
+}
+
+impl FromRequestParts for Version {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(parts.version)
+    }
+}
+
+impl<T: FromRequestParts> FromRequestParts for Option<T> {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(T::from_request_parts(parts).ok())
+    }
+}
+
+impl<T: FromRequestParts> FromRequestParts for Result<T, Rejection> {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        Ok(T::from_request_parts(parts))
+    }
+}
+
+impl<ReqBody: Send + 'static> FromRequest<ReqBody> for Request<ReqBody> {
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(async move { Ok(req) })
+    }
+}
+
+impl<ReqBody> FromRequest<ReqBody> for Bytes
+where
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(read_body(req))
+    }
+}
+
+impl<ReqBody> FromRequest<ReqBody> for String
+where
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        let bytes = Bytes::from_request(req);
+
+        Box::pin(async move {
+            String::from_utf8(bytes.await?.to_vec()).map_err(|e| {
+                Rejection::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("The request body isn't a valid UTF-8 string: {}", e),
+                )
+            })
+        })
+    }
+}
+
+/// The maximum size of the request bodies read by the extractors, it defaults to 2 MiB.
+///
+/// Share it via the [`data`](../struct.RouterBuilder.html#method.data) method to change the limit of a router or a
+/// scope. The requests with a larger body are rejected with `413 Payload Too Large`.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{body::Bytes, Body, Response};
+/// use routerify::extract::BodyLimit;
+/// use routerify::{RouteError, Router};
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .data(BodyLimit(64 * 1024))
+///     .post("/upload", |body: Bytes| async move { Ok(format!("Received {} bytes", body.len())) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit(pub usize);
+
+impl Default for BodyLimit {
+    fn default() -> Self {
+        BodyLimit(2 * 1024 * 1024)
+    }
+}
+
+// Reads the whole body, but no more than the body limit of the router.
+pub(crate) async fn read_body<ReqBody>(req: Request<ReqBody>) -> Result<Bytes, Rejection>
+where
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    let limit = req.data::<BodyLimit>().copied().unwrap_or_default().0;
+    let too_large = || {
+        Rejection::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("The request body is larger than the limit of {} bytes", limit),
+        )
+    };
+
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<u64>().ok());
+    if matches!(content_length, Some(len) if len > limit as u64) {
+        return Err(too_large());
+    }
+
+    let body = req.into_body();
+    tokio::pin!(body);
+
+    let mut buf = Vec::new();
+    loop {
+        let mut chunk = match body.data().await {
+            Some(chunk) => chunk.map_err(|e| {
+                Rejection::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Couldn't read the request body: {}", e.into()),
+                )
+            })?,
+            None => break,
+        };
+
+        if buf.len() + chunk.remaining() > limit {
+            return Err(too_large());
+        }
+
+        while chunk.has_remaining() {
+            let len = chunk.chunk().len();
+            buf.extend_from_slice(chunk.chunk());
+            chunk.advance(len);
+        }
+    }
+
+    Ok(Bytes::from(buf))
+}
+
+// Returns the lowercased media type of the request without its parameters e.g. the charset.
+#[cfg(feature = "serde")]
+fn mime_type(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+}
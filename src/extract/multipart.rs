@@ -0,0 +1,502 @@
+use super::{ExtractFuture, FromRequest, Rejection};
+use crate::ext::RequestExt;
+use crate::RouteError;
+use futures_core::Stream;
+use hyper::body::{Buf, Bytes, HttpBody};
+use hyper::{header, HeaderMap, Request, StatusCode};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// The limits and the spilling of the [`Multipart`](./struct.Multipart.html) extractor, it's shared via the
+/// [`data`](../struct.RouterBuilder.html#method.data) method of the router or its scopes.
+///
+/// The body of the request is limited by the total limit and every field by the field limit, these replace the
+/// [`BodyLimit`](./struct.BodyLimit.html) for the multipart requests. The file parts which are larger than the memory
+/// threshold are written to a temporary file by [`Field::data`](./struct.Field.html#method.data).
+///
+/// # Examples
+///
+/// ```
+/// use hyper::Body;
+/// use routerify::extract::MultipartConfig;
+/// use routerify::{RouteError, Router};
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .data(
+///         MultipartConfig::new()
+///             .total_limit(64 * 1024 * 1024)
+///             .field_limit(16 * 1024 * 1024)
+///             .memory_threshold(1024 * 1024),
+///     )
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultipartConfig {
+    total_limit: u64,
+    field_limit: u64,
+    memory_threshold: usize,
+    temp_dir: Option<PathBuf>,
+}
+
+impl MultipartConfig {
+    /// Creates the default config, the total limit is 32 MiB, the field limit is 8 MiB and the memory threshold is
+    /// 256 KiB.
+    pub fn new() -> MultipartConfig {
+        MultipartConfig {
+            total_limit: 32 * 1024 * 1024,
+            field_limit: 8 * 1024 * 1024,
+            memory_threshold: 256 * 1024,
+            temp_dir: None,
+        }
+    }
+
+    /// Sets the maximum size of the whole body in bytes.
+    pub fn total_limit(mut self, limit: u64) -> Self {
+        self.total_limit = limit;
+        self
+    }
+
+    /// Sets the maximum size of every field in bytes, including the file parts.
+    pub fn field_limit(mut self, limit: u64) -> Self {
+        self.field_limit = limit;
+        self
+    }
+
+    /// Sets the size in bytes above which the file parts are written to a temporary file.
+    pub fn memory_threshold(mut self, threshold: usize) -> Self {
+        self.memory_threshold = threshold;
+        self
+    }
+
+    /// Sets the directory of the temporary files, it's the [`std::env::temp_dir`] by default.
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        MultipartConfig::new()
+    }
+}
+
+/// Extracts a `multipart/form-data` request body, the fields are streamed out of the body one by one.
+///
+/// The request must have the `multipart/form-data` content type with a boundary, otherwise it's rejected with
+/// `415 Unsupported Media Type`. The limits are configured with the [`MultipartConfig`](./struct.MultipartConfig.html),
+/// a field or a body exceeding them fails with a [`MultipartError`](./enum.MultipartError.html).
+///
+/// It's available with the `multipart` feature.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::Body;
+/// use routerify::extract::{FieldData, Multipart, Rejection};
+/// use routerify::{RouteError, Router};
+/// use std::path::Path;
+///
+/// async fn upload(mut multipart: Multipart) -> Result<String, RouteError> {
+///     let mut text = String::new();
+///
+///     while let Some(field) = multipart.next_field().await.map_err(Rejection::from)? {
+///         let name = field.name().unwrap_or_default().to_owned();
+///
+///         match field.data().await.map_err(Rejection::from)? {
+///             FieldData::Memory(bytes) => text.push_str(&format!("{}: {} bytes\n", name, bytes.len())),
+///             FieldData::File(file) => {
+///                 text.push_str(&format!("{}: {} bytes on disk\n", name, file.len()));
+///                 // The field and file names come from the client, so the file keeps its generated name.
+///                 let dest = Path::new("/tmp/uploads").join(file.path().file_name().unwrap());
+///                 file.persist(dest).await?;
+///             }
+///         }
+///     }
+///
+///     Ok(text)
+/// }
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .post("/upload", upload)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub struct Multipart {
+    inner: multer::Multipart<'static>,
+    config: MultipartConfig,
+}
+
+impl Multipart {
+    /// Creates the multipart stream from a request, with the [`MultipartConfig`](./struct.MultipartConfig.html) shared
+    /// with the router or the default one.
+    pub fn new<ReqBody>(req: Request<ReqBody>) -> Result<Multipart, MultipartError>
+    where
+        ReqBody: HttpBody + Send + 'static,
+        ReqBody::Error: Into<RouteError>,
+    {
+        let config = req.data::<MultipartConfig>().cloned().unwrap_or_default();
+
+        let boundary = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| multer::parse_boundary(val).ok())
+            .ok_or(MultipartError::UnsupportedMediaType)?;
+
+        let constraints = multer::Constraints::new().size_limit(
+            multer::SizeLimit::new()
+                .whole_stream(config.total_limit)
+                .per_field(config.field_limit),
+        );
+
+        let stream = BodyStream(Box::pin(req.into_body()));
+
+        Ok(Multipart {
+            inner: multer::Multipart::with_constraints(stream, boundary, constraints),
+            config,
+        })
+    }
+
+    /// Returns the next field of the body, or `None` after the last one.
+    ///
+    /// The previous field must be dropped before the next one is read.
+    pub async fn next_field(&mut self) -> Result<Option<Field>, MultipartError> {
+        let field = self.inner.next_field().await.map_err(MultipartError::from)?;
+
+        Ok(field.map(|inner| Field {
+            inner,
+            config: self.config.clone(),
+        }))
+    }
+}
+
+impl Debug for Multipart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multipart").field("config", &self.config).finish()
+    }
+}
+
+impl<ReqBody> FromRequest<ReqBody> for Multipart
+where
+    ReqBody: HttpBody + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<RouteError>,
+{
+    fn from_request(req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(std::future::ready(Multipart::new(req).map_err(Rejection::from)))
+    }
+}
+
+/// A field of a [`Multipart`](./struct.Multipart.html) body, either a plain value or a file part.
+pub struct Field {
+    inner: multer::Field<'static>,
+    config: MultipartConfig,
+}
+
+impl Field {
+    /// Returns the name of the field.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// Returns the file name of a file part, as sent by the client.
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+
+    /// Returns the content type of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.inner.content_type().map(|mime| mime.as_ref())
+    }
+
+    /// Returns the headers of the field.
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Returns the next chunk of the field, or `None` at the end of it.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        self.inner.chunk().await.map_err(MultipartError::from)
+    }
+
+    /// Reads the whole field into memory.
+    pub async fn bytes(self) -> Result<Bytes, MultipartError> {
+        self.inner.bytes().await.map_err(MultipartError::from)
+    }
+
+    /// Reads the whole field into memory as text, it's decoded with the charset of the field or as UTF-8.
+    pub async fn text(self) -> Result<String, MultipartError> {
+        self.inner.text().await.map_err(MultipartError::from)
+    }
+
+    /// Reads the whole field, a file part larger than the memory threshold of the
+    /// [`MultipartConfig`](./struct.MultipartConfig.html) is written to a [`TempFile`](./struct.TempFile.html).
+    pub async fn data(mut self) -> Result<FieldData, MultipartError> {
+        let spillable = self.file_name().is_some();
+        let mut buf = Vec::new();
+
+        while let Some(chunk) = self.chunk().await? {
+            if spillable && buf.len() + chunk.len() > self.config.memory_threshold {
+                buf.extend_from_slice(&chunk);
+                return self.spill(buf).await.map(FieldData::File);
+            }
+
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(FieldData::Memory(Bytes::from(buf)))
+    }
+
+    async fn spill(mut self, buf: Vec<u8>) -> Result<TempFile, MultipartError> {
+        let dir = self.config.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+        let (mut file, mut temp_file) = TempFile::create(&dir).await?;
+
+        file.write_all(&buf).await?;
+        temp_file.len = buf.len() as u64;
+
+        while let Some(chunk) = self.chunk().await? {
+            file.write_all(&chunk).await?;
+            temp_file.len += chunk.len() as u64;
+        }
+
+        file.flush().await?;
+
+        Ok(temp_file)
+    }
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name())
+            .field("file_name", &self.file_name())
+            .field("content_type", &self.content_type())
+            .finish()
+    }
+}
+
+/// The content of a [`Field`](./struct.Field.html) read by [`Field::data`](./struct.Field.html#method.data).
+#[derive(Debug)]
+pub enum FieldData {
+    /// The field is kept in memory.
+    Memory(Bytes),
+    /// The file part was written to a temporary file.
+    File(TempFile),
+}
+
+/// A temporary file holding a file part, it's removed when dropped unless it's persisted.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    len: u64,
+    persisted: bool,
+}
+
+impl TempFile {
+    async fn create(dir: &Path) -> io::Result<(File, TempFile)> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let name = format!(
+            "routerify-multipart-{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        );
+        let path = dir.join(name);
+
+        let file = OpenOptions::new().write(true).create_new(true).open(&path).await?;
+
+        Ok((
+            file,
+            TempFile {
+                path,
+                len: 0,
+                persisted: false,
+            },
+        ))
+    }
+
+    /// Returns the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Returns the size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the file has no content.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Moves the file to the specified path, so it's kept after the request. It's copied if it can't be renamed e.g.
+    /// across file systems.
+    ///
+    /// The path shouldn't be built from the client input e.g. the field or file name of the part, as it could point
+    /// anywhere on the server.
+    pub async fn persist<P: AsRef<Path>>(mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+
+        if fs::rename(&self.path, path).await.is_err() {
+            fs::copy(&self.path, path).await?;
+            fs::remove_file(&self.path).await?;
+        }
+
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            if let Err(err) = std::fs::remove_file(&self.path) {
+                log::warn!("Couldn't remove the temporary file {}: {}", self.path.display(), err);
+            }
+        }
+    }
+}
+
+/// The error returned when a multipart body couldn't be read.
+///
+/// It can be converted into a [`Rejection`](./struct.Rejection.html), so the default error handler of the router responds
+/// with its [`status`](#method.status). Otherwise, a custom error handler can downcast the
+/// [`RouteError`](../type.RouteError.html) to it.
+pub enum MultipartError {
+    /// The request doesn't have the `multipart/form-data` content type with a boundary.
+    UnsupportedMediaType,
+    /// A field exceeded the field limit.
+    FieldTooLarge {
+        /// The name of the field.
+        field_name: Option<String>,
+        /// The limit in bytes.
+        limit: u64,
+    },
+    /// The body exceeded the total limit.
+    TooLarge {
+        /// The limit in bytes.
+        limit: u64,
+    },
+    /// The body isn't valid multipart data or it couldn't be read.
+    Malformed(String),
+    /// A file part couldn't be written to a temporary file.
+    Io(io::Error),
+}
+
+impl MultipartError {
+    /// Returns the status of the response which should be sent.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            MultipartError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            MultipartError::FieldTooLarge { .. } | MultipartError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            MultipartError::Malformed(_) => StatusCode::BAD_REQUEST,
+            MultipartError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<multer::Error> for MultipartError {
+    fn from(err: multer::Error) -> Self {
+        match err {
+            multer::Error::FieldSizeExceeded { limit, field_name } => {
+                MultipartError::FieldTooLarge { field_name, limit }
+            }
+            multer::Error::StreamSizeExceeded { limit } => MultipartError::TooLarge { limit },
+            // The stream limit is reported as a failure of reading the stream.
+            multer::Error::StreamReadFailed(err) => match err.downcast::<multer::Error>() {
+                Ok(err) => MultipartError::from(*err),
+                Err(err) => MultipartError::Malformed(format!("Couldn't read the request body: {}", err)),
+            },
+            err => MultipartError::Malformed(err.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for MultipartError {
+    fn from(err: io::Error) -> Self {
+        MultipartError::Io(err)
+    }
+}
+
+impl From<MultipartError> for Rejection {
+    fn from(err: MultipartError) -> Rejection {
+        Rejection::from_error(err.status(), err)
+    }
+}
+
+impl Display for MultipartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::UnsupportedMediaType => {
+                write!(f, "Expected a request with the `multipart/form-data` content type")
+            }
+            MultipartError::FieldTooLarge {
+                field_name: Some(name),
+                limit,
+            } => write!(f, "The field `{}` is larger than the limit of {} bytes", name, limit),
+            MultipartError::FieldTooLarge {
+                field_name: None,
+                limit,
+            } => {
+                write!(f, "A field is larger than the limit of {} bytes", limit)
+            }
+            MultipartError::TooLarge { limit } => {
+                write!(f, "The request body is larger than the limit of {} bytes", limit)
+            }
+            MultipartError::Malformed(msg) => write!(f, "Couldn't parse the multipart request body: {}", msg),
+            MultipartError::Io(err) => write!(f, "Couldn't write the file part to a temporary file: {}", err),
+        }
+    }
+}
+
+impl Debug for MultipartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "routerify::MultipartError: {}", self)
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+// Streams the chunks of any body type into the multipart parser.
+struct BodyStream<ReqBody>(Pin<Box<ReqBody>>);
+
+impl<ReqBody> Stream for BodyStream<ReqBody>
+where
+    ReqBody: HttpBody,
+    ReqBody::Error: Into<RouteError>,
+{
+    type Item = Result<Bytes, RouteError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_data(cx).map(|chunk| {
+            chunk.map(|chunk| {
+                chunk
+                    .map(|mut chunk| chunk.copy_to_bytes(chunk.remaining()))
+                    .map_err(Into::into)
+            })
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use super::{FromRequestParts, Rejection};
+use http::request::Parts;
+use hyper::StatusCode;
+use serde::de::DeserializeOwned;
+
+/// Extracts the query string of the request.
+///
+/// It's rejected with `400 Bad Request` if the query string doesn't match the type. Use `Option` fields for the
+/// optional parameters.
+///
+/// It's available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::Body;
+/// use routerify::extract::Query;
+/// use routerify::{RouteError, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Pagination {
+///     page: u32,
+///     per_page: Option<u32>,
+/// }
+///
+/// async fn list_users(Query(pagination): Query<Pagination>) -> Result<String, RouteError> {
+///     Ok(format!("Page {} of {} users", pagination.page, pagination.per_page.unwrap_or(20)))
+/// }
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router: Router<Body, RouteError> = Router::builder()
+///     .get("/users", list_users)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Query<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequestParts for Query<T> {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+
+        serde_urlencoded::from_str(query).map(Query).map_err(|e| {
+            Rejection::new(
+                StatusCode::BAD_REQUEST,
+                format!("Couldn't parse the query string: {}", e),
+            )
+        })
+    }
+}
@@ -0,0 +1,649 @@
+//! Static file and directory serving.
+//!
+//! This module is available with the `fs` feature. The [`StaticFiles`](./struct.StaticFiles.html) handler serves the
+//! files of a directory, its [`router`](./struct.StaticFiles.html#method.router) is mounted under a path with the
+//! [`scope`](../struct.RouterBuilder.html#method.scope) method of the router builder and the rest of the request path
+//! is resolved against the root directory.
+//!
+//! The `Content-Type` is guessed from the file extension, the responses carry an `ETag` and a `Last-Modified` header
+//! and the conditional requests are answered with `304 Not Modified`. A single byte range is served with
+//! `206 Partial Content`. The paths which would escape the root directory e.g. via `..` are treated as the missing files,
+//! which are passed to the `404 Not Found` route of the parent router. The single-page applications can set a
+//! [`fallback_file`](./struct.StaticFiles.html#method.fallback_file) which is served for the page navigations that
+//! don't match a file.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::{Body, Response};
+//! use routerify::fs::StaticFiles;
+//! use routerify::{RouteError, Router};
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .get("/", || async { Ok(Response::new(Body::from("Home page"))) })
+//!     // Serves `./public/css/app.css` at `/assets/css/app.css` and `./public/index.html` at `/assets/`.
+//!     .scope(
+//!         "/assets",
+//!         StaticFiles::new("./public")
+//!             .index_file("index.html")
+//!             .precompressed_gzip(true)
+//!             .router(),
+//!     )
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::ext::RequestExt;
+use crate::types::RouteParams;
+use crate::Router;
+use futures_core::Stream;
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Body, Method, Request, Response, StatusCode, Uri};
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncSeekExt, ReadBuf};
+
+// The size of the chunks the files are streamed with.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Serves the files of a directory, please refer to the [module documentation](./index.html) for an example.
+///
+/// The files are only read on the `GET` and `HEAD` requests, and the directories are only served through their index
+/// file. A request for a directory without the trailing slash is redirected to the path with it, so that the relative
+/// links of the index file keep working.
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    root: PathBuf,
+    index_file: Option<String>,
+    fallback_file: Option<String>,
+    precompressed_br: bool,
+    precompressed_gzip: bool,
+}
+
+impl StaticFiles {
+    /// Creates the handler for the specified root directory.
+    pub fn new<P: Into<PathBuf>>(root: P) -> StaticFiles {
+        StaticFiles {
+            root: root.into(),
+            index_file: None,
+            fallback_file: None,
+            precompressed_br: false,
+            precompressed_gzip: false,
+        }
+    }
+
+    /// Serves the specified file e.g. `index.html` for the requests of the directories which contain it.
+    pub fn index_file<S: Into<String>>(mut self, file_name: S) -> Self {
+        self.index_file = Some(file_name.into());
+        self
+    }
+
+    /// Serves the specified file, relative to the root directory, for the `GET` requests which don't match a file and
+    /// accept HTML i.e. list `text/html` in the `Accept` header. It's meant for the single-page applications doing the
+    /// routing in the browser, the missing assets and the API requests are still passed to the `404 Not Found` route of
+    /// the parent router.
+    ///
+    /// # Examples
+    ///
+    /// The missing routes of the API, which is scoped before the application, get the JSON response of the catch-all
+    /// route of the root router.
+    ///
+    /// ```
+    /// use hyper::{Body, Response, StatusCode};
+    /// use routerify::fs::StaticFiles;
+    /// use routerify::{RouteError, Router};
+    ///
+    /// fn api() -> Router<Body, RouteError> {
+    ///     Router::builder()
+    ///         .get("/users", || async { Ok(Response::new(Body::from("[]"))) })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let router: Router<Body, RouteError> = Router::builder()
+    ///     .scope("/app/api", api())
+    ///     // `/app/settings/profile` serves `./dist/index.html` to the browsers.
+    ///     .scope(
+    ///         "/app",
+    ///         StaticFiles::new("./dist")
+    ///             .index_file("index.html")
+    ///             .fallback_file("index.html")
+    ///             .router(),
+    ///     )
+    ///     .any(|| async {
+    ///         Ok((
+    ///             StatusCode::NOT_FOUND,
+    ///             [("content-type", "application/json")],
+    ///             r#"{"error":"Not Found"}"#,
+    ///         ))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn fallback_file<S: Into<String>>(mut self, file_name: S) -> Self {
+        self.fallback_file = Some(file_name.into());
+        self
+    }
+
+    /// Serves the `.br` sibling of a file, if it exists, to the clients which accept the `br` encoding.
+    pub fn precompressed_br(mut self, enable: bool) -> Self {
+        self.precompressed_br = enable;
+        self
+    }
+
+    /// Serves the `.gz` sibling of a file, if it exists, to the clients which accept the `gzip` encoding.
+    ///
+    /// The `.br` variant is preferred if both are enabled and accepted.
+    pub fn precompressed_gzip(mut self, enable: bool) -> Self {
+        self.precompressed_gzip = enable;
+        self
+    }
+
+    /// Creates a router which serves the files on the `GET` and `HEAD` requests of any path, it's supposed to be mounted
+    /// with the [`scope`](../struct.RouterBuilder.html#method.scope) method of the router builder.
+    pub fn router<E, ReqBody>(self) -> Router<Body, E, ReqBody>
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    {
+        let files = self.clone();
+
+        Router::builder()
+            .get_or_head("/*", move |req: Request<ReqBody>| {
+                let path = req.param("*").map(String::as_str).unwrap_or_default();
+                let res = self.serve(path, &req);
+                async move { Ok::<_, E>(res.await) }
+            })
+            // The requests which don't match a file nor get the fallback are passed to the parent router, so they get
+            // its `404 Not Found` response.
+            .guard(move |req: &Request<ReqBody>, params: &RouteParams| {
+                let files = files.clone();
+                let path = params.get("*").cloned().unwrap_or_default();
+                let uri = req.uri().clone();
+                let fallback = files.fallback_file.is_some() && is_page_navigation(req.method(), req.headers());
+
+                Box::pin(async move { fallback || !matches!(files.lookup(&path, &uri).await, Ok(Lookup::NotFound)) })
+            })
+            .build()
+            .expect("Couldn't create the static files router")
+    }
+
+    /// Serves the file at the specified path relative to the root directory, it's used to serve the files from a custom
+    /// route handler e.g. after an authorization check.
+    ///
+    /// The path is percent-decoded already, like the route parameters. Only the method, the URI and the headers of the
+    /// request are used, the trailing slash of the request path tells whether a directory was requested.
+    pub fn serve<ReqBody>(&self, path: &str, req: &Request<ReqBody>) -> impl Future<Output = Response<Body>> + Send {
+        let files = self.clone();
+        let path = path.to_owned();
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        let headers = req.headers().clone();
+
+        async move {
+            match files.respond(&path, &method, &uri, &headers).await {
+                Ok(res) => res,
+                Err(err) => {
+                    log::error!("Couldn't serve the static file `{}`: {}", path, err);
+                    status_response(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }
+    }
+
+    async fn respond(&self, path: &str, method: &Method, uri: &Uri, headers: &HeaderMap) -> io::Result<Response<Body>> {
+        if method != Method::GET && method != Method::HEAD {
+            return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+        }
+
+        match self.lookup(path, uri).await? {
+            Lookup::File(file_path, metadata) => self.send_file(file_path, metadata, method, headers).await,
+            Lookup::Redirect => Ok(redirect_to_dir(uri)),
+            Lookup::NotFound => {
+                let fallback_file = match self.fallback_file {
+                    Some(ref fallback_file) => fallback_file,
+                    None => return Ok(status_response(StatusCode::NOT_FOUND)),
+                };
+
+                // Only the page navigations get the fallback, e.g. a missing script is still a `404`.
+                let mut res = match resolve(&self.root, fallback_file) {
+                    Some(file_path) if is_page_navigation(method, headers) => {
+                        match metadata_if_exists(&file_path).await? {
+                            Some(metadata) if metadata.is_file() => {
+                                self.send_file(file_path, metadata, method, headers).await?
+                            }
+                            _ => {
+                                log::error!("The static fallback file `{}` doesn't exist", fallback_file);
+                                status_response(StatusCode::NOT_FOUND)
+                            }
+                        }
+                    }
+                    _ => status_response(StatusCode::NOT_FOUND),
+                };
+
+                res.headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("accept"));
+                Ok(res)
+            }
+        }
+    }
+
+    async fn lookup(&self, path: &str, uri: &Uri) -> io::Result<Lookup> {
+        let mut file_path = match resolve(&self.root, path) {
+            Some(file_path) => file_path,
+            None => return Ok(Lookup::NotFound),
+        };
+
+        let metadata = match metadata_if_exists(&file_path).await? {
+            Some(metadata) => metadata,
+            None => return Ok(Lookup::NotFound),
+        };
+
+        // The router appends a trailing slash to the matched path, so it's checked on the request path.
+        let is_dir_path = uri.path().ends_with('/');
+
+        if metadata.is_dir() {
+            if !is_dir_path {
+                return Ok(Lookup::Redirect);
+            }
+
+            let index_file = match self.index_file {
+                Some(ref index_file) => index_file,
+                None => return Ok(Lookup::NotFound),
+            };
+
+            file_path.push(index_file);
+            match metadata_if_exists(&file_path).await? {
+                Some(metadata) if metadata.is_file() => Ok(Lookup::File(file_path, metadata)),
+                _ => Ok(Lookup::NotFound),
+            }
+        } else if is_dir_path {
+            Ok(Lookup::NotFound)
+        } else {
+            Ok(Lookup::File(file_path, metadata))
+        }
+    }
+
+    async fn send_file(
+        &self,
+        mut file_path: PathBuf,
+        mut metadata: std::fs::Metadata,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> io::Result<Response<Body>> {
+        let content_type = content_type(&file_path);
+
+        let mut encoding = None;
+        for (enabled, name, ext) in [
+            (self.precompressed_br, "br", "br"),
+            (self.precompressed_gzip, "gzip", "gz"),
+        ] {
+            if !enabled || !accepts(headers, header::ACCEPT_ENCODING, &[name]) {
+                continue;
+            }
+
+            let mut encoded_path = file_path.clone().into_os_string();
+            encoded_path.push(".");
+            encoded_path.push(ext);
+
+            if let Some(encoded_metadata) = metadata_if_exists(Path::new(&encoded_path)).await? {
+                if encoded_metadata.is_file() {
+                    file_path = encoded_path.into();
+                    metadata = encoded_metadata;
+                    encoding = Some(name);
+                    break;
+                }
+            }
+        }
+
+        let len = metadata.len();
+        let modified = metadata.modified().ok();
+        let etag = etag(len, modified);
+        let last_modified = modified.map(httpdate::fmt_http_date);
+
+        let mut res = Response::new(Body::empty());
+        let res_headers = res.headers_mut();
+        res_headers.insert(header::CONTENT_TYPE, content_type);
+        res_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        res_headers.insert(header::ETAG, header_value(&etag));
+        if let Some(ref last_modified) = last_modified {
+            res_headers.insert(header::LAST_MODIFIED, header_value(last_modified));
+        }
+        if self.precompressed_br || self.precompressed_gzip {
+            res_headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+        }
+        if let Some(encoding) = encoding {
+            res_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+        }
+
+        if is_not_modified(headers, &etag, modified) {
+            res.headers_mut().remove(header::CONTENT_TYPE);
+            *res.status_mut() = StatusCode::NOT_MODIFIED;
+            return Ok(res);
+        }
+
+        let range = match headers.get(header::RANGE).and_then(|val| val.to_str().ok()) {
+            Some(range) if if_range_matches(headers, &etag, last_modified.as_deref()) => parse_range(range, len),
+            _ => ByteRange::Full,
+        };
+
+        let (start, end) = match range {
+            ByteRange::Full => (0, len),
+            ByteRange::Partial(start, end) => {
+                *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+                res.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    header_value(&format!("bytes {}-{}/{}", start, end - 1, len)),
+                );
+                (start, end)
+            }
+            ByteRange::Unsatisfiable => {
+                let mut res = status_response(StatusCode::RANGE_NOT_SATISFIABLE);
+                res.headers_mut()
+                    .insert(header::CONTENT_RANGE, header_value(&format!("bytes */{}", len)));
+                return Ok(res);
+            }
+        };
+
+        res.headers_mut()
+            .insert(header::CONTENT_LENGTH, HeaderValue::from(end - start));
+
+        if method == Method::GET {
+            let mut file = match File::open(&file_path).await {
+                Ok(file) => file,
+                Err(err) if is_not_found(&err) => return Ok(status_response(StatusCode::NOT_FOUND)),
+                Err(err) => return Err(err),
+            };
+            if start > 0 {
+                file.seek(SeekFrom::Start(start)).await?;
+            }
+
+            *res.body_mut() = Body::wrap_stream(FileStream {
+                file,
+                remaining: end - start,
+                buf: Vec::new(),
+            });
+        }
+
+        Ok(res)
+    }
+}
+
+// Joins the path to the root if it only consists of normal components i.e. it can't escape the root.
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let mut file_path = root.to_path_buf();
+
+    for segment in path.split('/').filter(|segment| !segment.is_empty() && *segment != ".") {
+        if segment.contains(['\\', '\0']) {
+            return None;
+        }
+
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => file_path.push(name),
+            _ => return None,
+        }
+    }
+
+    Some(file_path)
+}
+
+async fn metadata_if_exists(path: &Path) -> io::Result<Option<std::fs::Metadata>> {
+    match fs::metadata(path).await {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(err) if is_not_found(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn is_not_found(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory | io::ErrorKind::PermissionDenied
+    )
+}
+
+fn content_type(path: &Path) -> HeaderValue {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+
+    if mime.type_() == mime_guess::mime::TEXT && mime.get_param(mime_guess::mime::CHARSET).is_none() {
+        header_value(&format!("{}; charset=utf-8", mime))
+    } else {
+        header_value(mime.as_ref())
+    }
+}
+
+fn etag(len: u64, modified: Option<SystemTime>) -> String {
+    let modified = modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_nanos())
+        .unwrap_or_default();
+
+    format!("\"{:x}-{:x}\"", modified, len)
+}
+
+// Checks the `If-None-Match` header, or the `If-Modified-Since` header if the former is missing.
+fn is_not_modified(headers: &HeaderMap, etag: &str, modified: Option<SystemTime>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        return matches!(if_none_match.to_str(), Ok(val) if val.split(',').map(str::trim).any(|tag| {
+            tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag.strip_prefix("W/").unwrap_or(etag)
+        }));
+    }
+
+    let if_modified_since = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| httpdate::parse_http_date(val).ok());
+
+    match (modified, if_modified_since) {
+        (Some(modified), Some(since)) => {
+            // The HTTP dates don't have the sub-second precision.
+            let secs = |time: SystemTime| time.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+            secs(modified) <= secs(since)
+        }
+        _ => false,
+    }
+}
+
+// The range is only honored if the `If-Range` validator matches the current version of the file.
+fn if_range_matches(headers: &HeaderMap, etag: &str, last_modified: Option<&str>) -> bool {
+    match headers.get(header::IF_RANGE).and_then(|val| val.to_str().ok()) {
+        Some(if_range) => if_range == etag || Some(if_range) == last_modified,
+        None => true,
+    }
+}
+
+enum Lookup {
+    File(PathBuf, std::fs::Metadata),
+    // A directory was requested without the trailing slash.
+    Redirect,
+    NotFound,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    // The end is exclusive.
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+// Parses a single `bytes` range, the invalid and the multiple ranges are ignored i.e. the whole file is served.
+fn parse_range(range: &str, len: u64) -> ByteRange {
+    let spec = match range.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return ByteRange::Full,
+    };
+
+    let (start, end) = match spec.split_once('-') {
+        Some(bounds) => bounds,
+        None => return ByteRange::Full,
+    };
+
+    if start.is_empty() {
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Partial(len.saturating_sub(suffix), len),
+            Err(_) => ByteRange::Full,
+        };
+    }
+
+    let start = match start.parse::<u64>() {
+        Ok(start) => start,
+        Err(_) => return ByteRange::Full,
+    };
+    let end = match end {
+        "" => None,
+        end => match end.parse::<u64>() {
+            Ok(end) if end >= start => Some(end),
+            _ => return ByteRange::Full,
+        },
+    };
+
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+
+    ByteRange::Partial(start, end.map_or(len, |end| end.min(len - 1) + 1))
+}
+
+// The fallback file is only served to the `GET` requests accepting HTML, i.e. the page navigations of the browsers.
+fn is_page_navigation(method: &Method, headers: &HeaderMap) -> bool {
+    method == Method::GET && accepts(headers, header::ACCEPT, &["text/html", "application/xhtml+xml"])
+}
+
+// Checks if an `Accept` like header lists any of the values with a non-zero quality, the wildcards aren't matched.
+fn accepts(headers: &HeaderMap, name: header::HeaderName, values: &[&str]) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .any(|item| {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or_default().trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(1.0, |quality| quality.trim().parse::<f32>().unwrap_or(0.0));
+
+            values.iter().any(|val| name.eq_ignore_ascii_case(val)) && quality > 0.0
+        })
+}
+
+fn redirect_to_dir(uri: &Uri) -> Response<Body> {
+    let location = match uri.query() {
+        Some(query) => format!("{}/?{}", uri.path(), query),
+        None => format!("{}/", uri.path()),
+    };
+
+    let mut res = status_response(StatusCode::MOVED_PERMANENTLY);
+    res.headers_mut().insert(header::LOCATION, header_value(&location));
+    res
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(Body::from(status.canonical_reason().unwrap_or_default()))
+        .expect("Couldn't create the static files response")
+}
+
+fn header_value(val: &str) -> HeaderValue {
+    HeaderValue::from_str(val).expect("The static files headers are valid header values")
+}
+
+// Streams the remaining bytes of a file in chunks.
+struct FileStream {
+    file: File,
+    remaining: u64,
+    buf: Vec<u8>,
+}
+
+impl Stream for FileStream {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+
+        let chunk_size = this.remaining.min(CHUNK_SIZE as u64) as usize;
+        this.buf.resize(chunk_size, 0);
+
+        let mut buf = ReadBuf::new(&mut this.buf);
+        match Pin::new(&mut this.file).poll_read(cx, &mut buf) {
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => Poll::Ready(Some(Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The file was truncated while it was being sent",
+            )))),
+            Poll::Ready(Ok(())) => {
+                let chunk = Bytes::copy_from_slice(buf.filled());
+                this.remaining -= chunk.len() as u64;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let root = Path::new("/srv/public");
+        assert_eq!(resolve(root, ""), Some(PathBuf::from("/srv/public")));
+        assert_eq!(
+            resolve(root, "css//./app.css"),
+            Some(PathBuf::from("/srv/public/css/app.css"))
+        );
+        assert_eq!(resolve(root, "../secret"), None);
+        assert_eq!(resolve(root, "css/../../secret"), None);
+        assert_eq!(resolve(root, "css\\..\\secret"), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-4", 10), ByteRange::Partial(0, 5));
+        assert_eq!(parse_range("bytes=5-", 10), ByteRange::Partial(5, 10));
+        assert_eq!(parse_range("bytes=-3", 10), ByteRange::Partial(7, 10));
+        assert_eq!(parse_range("bytes=8-20", 10), ByteRange::Partial(8, 10));
+        assert_eq!(parse_range("bytes=10-", 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,4-5", 10), ByteRange::Full);
+        assert_eq!(parse_range("bytes=5-2", 10), ByteRange::Full);
+        assert_eq!(parse_range("items=0-1", 10), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_accepts() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip;q=0, BR; q=0.5"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/html;level=1, */*;q=0.8"));
+        assert!(accepts(&headers, header::ACCEPT_ENCODING, &["br"]));
+        assert!(!accepts(&headers, header::ACCEPT_ENCODING, &["gzip"]));
+        assert!(accepts(&headers, header::ACCEPT, &["text/html"]));
+        assert!(!accepts(&headers, header::ACCEPT, &["application/json"]));
+        assert!(!accepts(&HeaderMap::new(), header::ACCEPT_ENCODING, &["gzip"]));
+    }
+}
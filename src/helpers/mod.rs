@@ -1,7 +1,23 @@
 use crate::types::RequestMeta;
 use crate::Error;
+use http::uri::{PathAndQuery, Uri};
 use http::Extensions;
 use percent_encoding::percent_decode_str;
+#[cfg(any(feature = "lambda", feature = "cgi"))]
+use percent_encoding::{AsciiSet, CONTROLS};
+
+// The characters which are not allowed in a path, the `%` is kept as the paths may be already encoded.
+#[cfg(any(feature = "lambda", feature = "cgi"))]
+pub(crate) const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
 
 pub(crate) fn update_req_meta_in_extensions(ext: &mut Extensions, new_req_meta: RequestMeta) {
     if let Some(existing_req_meta) = ext.get_mut::<RequestMeta>() {
@@ -18,6 +34,40 @@ pub(crate) fn percent_decode_request_path(val: &str) -> crate::Result<String> {
         .map(|val| val.to_string())
 }
 
+pub(crate) fn strip_path_segments(uri: &Uri, segments: usize) -> crate::Result<Uri> {
+    let path = uri.path();
+
+    let mut idx = 0;
+    for _ in 0..segments {
+        match path[idx + 1..].find('/') {
+            Some(pos) => idx += pos + 1,
+            None => {
+                idx = path.len();
+                break;
+            }
+        }
+    }
+
+    let mut new_path = String::with_capacity(path.len() - idx + 1);
+    if !path[idx..].starts_with('/') {
+        new_path.push('/');
+    }
+    new_path.push_str(&path[idx..]);
+    if let Some(query) = uri.query() {
+        new_path.push('?');
+        new_path.push_str(query);
+    }
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        PathAndQuery::from_maybe_shared(new_path)
+            .map_err(|e| Error::new(format!("Couldn't strip the mount prefix from the request path: {}", e)))?,
+    );
+
+    Uri::from_parts(parts)
+        .map_err(|e| Error::new(format!("Couldn't strip the mount prefix from the request path: {}", e)).into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +89,19 @@ mod tests {
         let val = "go%crazy";
         assert_eq!(percent_decode_request_path(val).unwrap(), "go%crazy".to_owned());
     }
+
+    #[test]
+    fn test_strip_path_segments() {
+        let uri: Uri = "/admin/ui/index.html?tab=1".parse().unwrap();
+        assert_eq!(strip_path_segments(&uri, 1).unwrap(), "/ui/index.html?tab=1");
+        assert_eq!(strip_path_segments(&uri, 2).unwrap(), "/index.html?tab=1");
+        assert_eq!(strip_path_segments(&uri, 3).unwrap(), "/?tab=1");
+        assert_eq!(strip_path_segments(&uri, 0).unwrap(), "/admin/ui/index.html?tab=1");
+
+        let uri: Uri = "http://localhost/metrics".parse().unwrap();
+        assert_eq!(strip_path_segments(&uri, 1).unwrap(), "http://localhost/");
+
+        let uri: Uri = "/metrics/".parse().unwrap();
+        assert_eq!(strip_path_segments(&uri, 1).unwrap(), "/");
+    }
 }
@@ -0,0 +1,400 @@
+//! Support for [hyper 1.x](https://docs.rs/hyper/1) and its connection model.
+//!
+//! This module is available with the `hyper1` feature. hyper 1.x doesn't have the `Server` and the `AddrStream` types
+//! anymore, the connections are accepted by the application and served with the connection builders of
+//! [`hyper-util`](https://docs.rs/hyper-util). So, a [`RequestService`](./struct.RequestService.html) is created per
+//! connection with the [`RequestServiceBuilder::build_hyper1`](../struct.RequestServiceBuilder.html#method.build_hyper1)
+//! method and passed to the connection builder.
+//!
+//! The router itself stays the same: the requests are converted into `http` 0.2 requests before they reach the router
+//! and the responses are converted back into `http` 1.x responses. The request extensions of hyper 1.x are available
+//! as a whole as an `http::Extensions` of `http` 1.x in the request extensions, and the request body is converted via
+//! the [`FromIncoming`](./trait.FromIncoming.html) trait.
+//!
+//! The `hyper-http1` and the `hyper-http2` features enable the respective protocols of hyper 1.x too.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use hyper::{Body, Response};
+//! use hyper_util::rt::{TokioExecutor, TokioIo};
+//! use hyper_util::server::conn::auto::Builder;
+//! use routerify::{RequestServiceBuilder, Router};
+//! use std::convert::Infallible;
+//! use tokio::net::TcpListener;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router: Router<Body, Infallible> = Router::builder()
+//!         .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+//!         .build()
+//!         .unwrap();
+//!
+//!     let builder = RequestServiceBuilder::new(router).unwrap();
+//!     let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+//!
+//!     loop {
+//!         let (stream, remote_addr) = listener.accept().await.unwrap();
+//!         let service = builder.build_hyper1(remote_addr);
+//!
+//!         tokio::spawn(async move {
+//!             if let Err(err) = Builder::new(TokioExecutor::new())
+//!                 .serve_connection(TokioIo::new(stream), service)
+//!                 .await
+//!             {
+//!                 eprintln!("Connection error: {}", err);
+//!             }
+//!         });
+//!     }
+//! }
+//! ```
+
+use crate::router::Router;
+use crate::service::InFlightBody;
+use crate::types::ConnectionInfo;
+use crate::{Error, RouteError};
+use futures_core::Stream;
+use http_body_1::{Frame, SizeHint};
+use hyper::http::uri::{self, Authority, PathAndQuery, Scheme};
+use hyper::{body::HttpBody, service::Service, HeaderMap, Method, Request, Response, Uri, Version};
+use hyper_1::body::Incoming;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Converts the hyper 1.x request body into the request body type of the router.
+///
+/// It's implemented for [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html), so the routers with
+/// the default request body type work as is, and for the hyper 1.x `Incoming` body itself. The trailers are not
+/// available through the `hyper::Body`.
+pub trait FromIncoming {
+    /// Converts the hyper 1.x request body.
+    fn from_incoming(body: Incoming) -> Self;
+}
+
+impl FromIncoming for hyper::Body {
+    fn from_incoming(body: Incoming) -> Self {
+        hyper::Body::wrap_stream(IncomingStream { body })
+    }
+}
+
+impl FromIncoming for Incoming {
+    fn from_incoming(body: Incoming) -> Self {
+        body
+    }
+}
+
+struct IncomingStream {
+    body: Incoming,
+}
+
+impl Stream for IncomingStream {
+    type Item = Result<hyper::body::Bytes, hyper_1::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let frame = match http_body_1::Body::poll_frame(Pin::new(&mut self.body), cx) {
+                Poll::Ready(Some(Ok(frame))) => frame,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            // Skip the trailers frame as the `hyper::Body` stream consists of data only.
+            if let Ok(data) = frame.into_data() {
+                return Poll::Ready(Some(Ok(data)));
+            }
+        }
+    }
+}
+
+/// A hyper 1.x [`Service`](https://docs.rs/hyper/1/hyper/service/trait.Service.html) to process the incoming requests
+/// of a connection.
+///
+/// It's created by the [`RequestServiceBuilder::build_hyper1`](../struct.RequestServiceBuilder.html#method.build_hyper1)
+/// method.
+pub struct RequestService<B, ReqBody = hyper::Body> {
+    process: Process<B, ReqBody>,
+    connection_info: Arc<ConnectionInfo>,
+}
+
+// The router is hidden behind a closure so that the error type of the router doesn't take part in the bounds of the
+// hyper 1.x `Service` implementation.
+type Process<B, ReqBody> = Arc<
+    dyn Fn(Request<ReqBody>) -> Pin<Box<dyn Future<Output = crate::Result<Response<InFlightBody<B>>>> + Send + 'static>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+impl<B: HttpBody + Send + Sync + 'static, ReqBody: Send + 'static> RequestService<B, ReqBody> {
+    pub(crate) fn new<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>(
+        router: Arc<Router<B, E, ReqBody>>,
+        connection_info: Arc<ConnectionInfo>,
+    ) -> RequestService<B, ReqBody> {
+        let info = connection_info.clone();
+        let process: Process<B, ReqBody> = Arc::new(move |req| {
+            let mut service = crate::RequestService {
+                router: router.clone(),
+                connection_info: info.clone(),
+            };
+            service.call(req)
+        });
+
+        RequestService {
+            process,
+            connection_info,
+        }
+    }
+}
+
+impl<B, ReqBody> Clone for RequestService<B, ReqBody> {
+    fn clone(&self) -> Self {
+        RequestService {
+            process: self.process.clone(),
+            connection_info: self.connection_info.clone(),
+        }
+    }
+}
+
+impl<B: HttpBody + Send + Sync + 'static, ReqBody: FromIncoming + Send + 'static>
+    hyper_1::service::Service<http_1::Request<Incoming>> for RequestService<B, ReqBody>
+{
+    type Response = http_1::Response<ResponseBody<InFlightBody<B>>>;
+    type Error = RouteError;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn call(&self, req: http_1::Request<Incoming>) -> Self::Future {
+        let process = self.process.clone();
+
+        Box::pin(async move {
+            let req = convert_request(req)?.map(ReqBody::from_incoming);
+            let resp = process(req).await?;
+            Ok(convert_response(resp)?)
+        })
+    }
+}
+
+impl<B, ReqBody> Debug for RequestService<B, ReqBody> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ ConnectionInfo: {:?} }}", self.connection_info)
+    }
+}
+
+/// A hyper 1.x response body wrapping the response body of the router.
+///
+/// It fails with an error if the trailers of the response body can't be converted.
+pub struct ResponseBody<B> {
+    inner: Pin<Box<B>>,
+    data_done: bool,
+    trailers_done: bool,
+}
+
+impl<B> ResponseBody<B> {
+    fn new(inner: B) -> ResponseBody<B> {
+        ResponseBody {
+            inner: Box::pin(inner),
+            data_done: false,
+            trailers_done: false,
+        }
+    }
+}
+
+impl<B: HttpBody> http_body_1::Body for ResponseBody<B>
+where
+    B::Error: Into<RouteError>,
+{
+    type Data = B::Data;
+    type Error = RouteError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if !self.data_done {
+            match self.inner.as_mut().poll_data(cx) {
+                Poll::Ready(Some(Ok(data))) => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => self.data_done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        if self.trailers_done {
+            return Poll::Ready(None);
+        }
+
+        match self.inner.as_mut().poll_trailers(cx) {
+            Poll::Ready(Ok(trailers)) => {
+                self.trailers_done = true;
+                Poll::Ready(
+                    trailers.map(|trailers| convert_headers(&trailers).map(Frame::trailers).map_err(Into::into)),
+                )
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.trailers_done || self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let hint = self.inner.size_hint();
+        let mut size_hint = SizeHint::new();
+        size_hint.set_lower(hint.lower());
+        if let Some(upper) = hint.upper() {
+            size_hint.set_upper(upper);
+        }
+        size_hint
+    }
+}
+
+impl<B> Debug for ResponseBody<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ResponseBody")
+    }
+}
+
+fn convert_request<T>(req: http_1::Request<T>) -> Result<Request<T>, Error> {
+    let (parts, body) = req.into_parts();
+
+    let mut req = Request::new(body);
+    *req.method_mut() = convert_method(&parts.method)?;
+    *req.uri_mut() = convert_uri(parts.uri)?;
+    *req.version_mut() = convert_version(parts.version)?;
+    *req.headers_mut() = convert_request_headers(&parts.headers)?;
+    // The extensions are type-erased, so they're carried over as a whole.
+    req.extensions_mut().insert(parts.extensions);
+
+    Ok(req)
+}
+
+fn convert_method(method: &http_1::Method) -> Result<Method, Error> {
+    let method = match *method {
+        http_1::Method::GET => Method::GET,
+        http_1::Method::POST => Method::POST,
+        http_1::Method::PUT => Method::PUT,
+        http_1::Method::DELETE => Method::DELETE,
+        http_1::Method::HEAD => Method::HEAD,
+        http_1::Method::OPTIONS => Method::OPTIONS,
+        http_1::Method::CONNECT => Method::CONNECT,
+        http_1::Method::PATCH => Method::PATCH,
+        http_1::Method::TRACE => Method::TRACE,
+        ref method => Method::from_bytes(method.as_str().as_bytes())
+            .map_err(|e| Error::new(format!("Couldn't convert the request method: {}", e)))?,
+    };
+    Ok(method)
+}
+
+fn convert_uri(uri: http_1::Uri) -> Result<Uri, Error> {
+    let parts = uri.into_parts();
+
+    let mut converted = uri::Parts::default();
+    converted.scheme = parts
+        .scheme
+        .map(|scheme| Scheme::try_from(scheme.as_str()))
+        .transpose()
+        .map_err(|e| Error::new(format!("Couldn't convert the request uri scheme: {}", e)))?;
+    converted.authority = parts
+        .authority
+        .map(|authority| Authority::try_from(authority.as_str()))
+        .transpose()
+        .map_err(|e| Error::new(format!("Couldn't convert the request uri authority: {}", e)))?;
+    converted.path_and_query = parts
+        .path_and_query
+        .map(|path_and_query| PathAndQuery::try_from(path_and_query.as_str()))
+        .transpose()
+        .map_err(|e| Error::new(format!("Couldn't convert the request uri path: {}", e)))?;
+
+    Uri::from_parts(converted).map_err(|e| Error::new(format!("Couldn't convert the request uri: {}", e)))
+}
+
+fn convert_version(version: http_1::Version) -> Result<Version, Error> {
+    match version {
+        http_1::Version::HTTP_09 => Ok(Version::HTTP_09),
+        http_1::Version::HTTP_10 => Ok(Version::HTTP_10),
+        http_1::Version::HTTP_11 => Ok(Version::HTTP_11),
+        http_1::Version::HTTP_2 => Ok(Version::HTTP_2),
+        http_1::Version::HTTP_3 => Ok(Version::HTTP_3),
+        version => Err(Error::new(format!(
+            "Couldn't convert the request version: {:?}",
+            version
+        ))),
+    }
+}
+
+fn convert_request_headers(headers: &http_1::HeaderMap) -> Result<HeaderMap, Error> {
+    let mut converted = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers.iter() {
+        let name = hyper::header::HeaderName::from_bytes(name.as_str().as_bytes())
+            .map_err(|e| Error::new(format!("Couldn't convert the request header name {}: {}", name, e)))?;
+        let value = hyper::header::HeaderValue::from_bytes(value.as_bytes())
+            .map_err(|e| Error::new(format!("Couldn't convert the request header {}: {}", name, e)))?;
+        converted.append(name, value);
+    }
+    Ok(converted)
+}
+
+fn convert_response<B>(resp: hyper::Response<B>) -> Result<http_1::Response<ResponseBody<B>>, Error> {
+    let (parts, body) = resp.into_parts();
+
+    let mut resp = http_1::Response::builder()
+        .status(parts.status.as_u16())
+        .body(ResponseBody::new(body))
+        .map_err(|e| Error::new(format!("Couldn't convert the response: {}", e)))?;
+    *resp.headers_mut() = convert_headers(&parts.headers)?;
+
+    Ok(resp)
+}
+
+fn convert_headers(headers: &HeaderMap) -> Result<http_1::HeaderMap, Error> {
+    let mut converted = http_1::HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers.iter() {
+        let name = http_1::HeaderName::from_bytes(name.as_str().as_bytes())
+            .map_err(|e| Error::new(format!("Couldn't convert the response header name {}: {}", name, e)))?;
+        let value = http_1::HeaderValue::from_bytes(value.as_bytes())
+            .map_err(|e| Error::new(format!("Couldn't convert the response header {}: {}", name, e)))?;
+        converted.append(name, value);
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Tag(&'static str);
+
+    #[test]
+    fn test_convert_request() {
+        let mut req = http_1::Request::builder()
+            .method("PROPFIND")
+            .uri("https://example.com:8443/users/alice?page=2")
+            .version(http_1::Version::HTTP_2)
+            .header("x-tag", "a")
+            .header("x-tag", &b"\xfe"[..])
+            .body(())
+            .unwrap();
+        req.extensions_mut().insert(Tag("hyper1"));
+
+        let req = convert_request(req).unwrap();
+        assert_eq!(req.method().as_str(), "PROPFIND");
+        assert_eq!(req.uri(), "https://example.com:8443/users/alice?page=2");
+        assert_eq!(req.version(), Version::HTTP_2);
+        let tags: Vec<_> = req.headers().get_all("x-tag").iter().map(|v| v.as_bytes()).collect();
+        assert_eq!(tags, [&b"a"[..], &b"\xfe"[..]]);
+        let extensions = req.extensions().get::<http_1::Extensions>().unwrap();
+        assert_eq!(extensions.get::<Tag>(), Some(&Tag("hyper1")));
+
+        let req = convert_request(http_1::Request::builder().uri("*").body(()).unwrap()).unwrap();
+        assert_eq!(req.method(), Method::GET);
+        assert_eq!(req.uri(), "*");
+    }
+}
@@ -0,0 +1,392 @@
+//! An adapter to serve a router on [AWS Lambda](https://aws.amazon.com/lambda/) behind API Gateway or an Application
+//! Load Balancer.
+//!
+//! This module is available with the `lambda` feature. It works with the raw JSON events, so it can be used with any
+//! Lambda runtime e.g. [`lambda_runtime`](https://docs.rs/lambda_runtime). The API Gateway REST API (payload format
+//! 1.0), the API Gateway HTTP API (payload format 2.0) and the Application Load Balancer events are supported, and the
+//! response is returned in the format of the received event.
+//!
+//! The remote address of the requests is the source IP of the request context, or the last `X-Forwarded-For` entry for
+//! the Application Load Balancer events.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use hyper::{Body, Response};
+//! use lambda_runtime::{service_fn, LambdaEvent};
+//! use routerify::lambda::LambdaHandler;
+//! use routerify::{RouteError, Router};
+//! use serde_json::Value;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), RouteError> {
+//!     let router: Router<Body, RouteError> = Router::builder()
+//!         .get("/", || async move { Ok(Response::new(Body::from("Hello, world!"))) })
+//!         .build()?;
+//!
+//!     let handler = Arc::new(LambdaHandler::new(router)?);
+//!
+//!     lambda_runtime::run(service_fn(move |event: LambdaEvent<Value>| {
+//!         let handler = handler.clone();
+//!         async move { handler.handle(event.payload).await }
+//!     }))
+//!     .await
+//! }
+//! ```
+
+use crate::helpers;
+use crate::service::RequestServiceBuilder;
+use crate::types::{ConnectionInfo, PeerAddr};
+use crate::{Error, RouteError, Router};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hyper::body::{self, HttpBody};
+use hyper::header::{HeaderName, HeaderValue, CONTENT_ENCODING, COOKIE, SET_COOKIE};
+use hyper::service::Service;
+use hyper::{Body, HeaderMap, Request, Response};
+use percent_encoding::{utf8_percent_encode, AsciiSet};
+use serde_json::{json, Map, Value};
+use std::fmt::{self, Debug, Formatter};
+use std::net::{IpAddr, SocketAddr};
+
+const QUERY_COMPONENT: &AsciiSet = &helpers::PATH_ENCODE_SET
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'=')
+    .add(b'/');
+// The API Gateway REST API decodes the path, so a `%` in it is a literal one.
+const DECODED_PATH: &AsciiSet = &helpers::PATH_ENCODE_SET.add(b'%');
+
+/// The kind of a Lambda event, it decides the format of the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// An API Gateway REST API event, or an HTTP API event with the payload format 1.0.
+    ApiGatewayV1,
+    /// An API Gateway HTTP API event with the payload format 2.0.
+    ApiGatewayV2,
+    /// An Application Load Balancer event, the flag tells whether the multi-value headers are enabled for the target
+    /// group.
+    Alb {
+        /// Whether the multi-value headers are enabled.
+        multi_value_headers: bool,
+    },
+}
+
+/// Dispatches the Lambda events through a router.
+///
+/// Please refer to the [module](./index.html) documentation for an example.
+pub struct LambdaHandler<B, E> {
+    builder: RequestServiceBuilder<B, E>,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    LambdaHandler<B, E>
+{
+    /// Creates a new handler for the router.
+    pub fn new(router: Router<B, E>) -> crate::Result<LambdaHandler<B, E>> {
+        Ok(LambdaHandler {
+            builder: RequestServiceBuilder::new(router)?,
+        })
+    }
+}
+
+impl<B, E> LambdaHandler<B, E>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    B::Error: Into<RouteError>,
+{
+    /// Converts the event into a request, dispatches it through the router and converts the response back into the
+    /// format of the event.
+    pub async fn handle(&self, event: Value) -> crate::Result<Value> {
+        let (mut req, kind) = into_request(event)?;
+
+        let connection_info = req
+            .extensions_mut()
+            .remove::<ConnectionInfo>()
+            .unwrap_or_else(|| ConnectionInfo::new(PeerAddr::Unknown));
+
+        let mut service = self.builder.build(connection_info);
+        let res = service.call(req).await?;
+
+        from_response(res, kind).await
+    }
+}
+
+impl<B, E> Debug for LambdaHandler<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "LambdaHandler")
+    }
+}
+
+/// Converts an API Gateway or an Application Load Balancer event into a request.
+///
+/// The [connection info](../struct.ConnectionInfo.html) with the remote address is added as a request extension.
+pub fn into_request(event: Value) -> crate::Result<(Request<Body>, EventKind)> {
+    let kind = event_kind(&event)?;
+    let context = &event["requestContext"];
+
+    let (method, path, query) = match kind {
+        EventKind::ApiGatewayV2 => (
+            str_field(&context["http"], "method")?,
+            utf8_percent_encode(str_field(&event, "rawPath")?, helpers::PATH_ENCODE_SET).to_string(),
+            event["rawQueryString"].as_str().unwrap_or_default().to_owned(),
+        ),
+        EventKind::ApiGatewayV1 => (
+            str_field(&event, "httpMethod")?,
+            utf8_percent_encode(str_field(&event, "path")?, DECODED_PATH).to_string(),
+            encode_query_params(&event),
+        ),
+        // The Application Load Balancer doesn't decode the query parameters.
+        EventKind::Alb { .. } => (
+            str_field(&event, "httpMethod")?,
+            utf8_percent_encode(str_field(&event, "path")?, helpers::PATH_ENCODE_SET).to_string(),
+            query_params(&event)
+                .map(|(key, val)| format!("{}={}", key, val))
+                .collect::<Vec<String>>()
+                .join("&"),
+        ),
+    };
+
+    let mut uri = path;
+    if !query.is_empty() {
+        uri.push('?');
+        uri.push_str(&query);
+    }
+
+    let mut builder = Request::builder().method(method).uri(uri);
+    let headers = builder
+        .headers_mut()
+        .ok_or_else(|| Error::new("Couldn't create a request from the Lambda event"))?;
+    read_headers(&event, headers)?;
+
+    if let Some(cookies) = event["cookies"].as_array() {
+        let cookies = cookies
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<&str>>()
+            .join("; ");
+        if !cookies.is_empty() {
+            headers.insert(COOKIE, HeaderValue::from_str(&cookies)?);
+        }
+    }
+
+    let remote_addr = remote_addr(&event, kind, headers);
+
+    let body = match event["body"].as_str() {
+        Some(body) if event["isBase64Encoded"].as_bool().unwrap_or(false) => Body::from(
+            BASE64
+                .decode(body)
+                .map_err(|e| Error::new(format!("Couldn't decode the Lambda event body: {}", e)))?,
+        ),
+        Some(body) => Body::from(body.to_owned()),
+        None => Body::empty(),
+    };
+
+    let mut req = builder.body(body)?;
+    req.extensions_mut().insert(ConnectionInfo::new(remote_addr));
+
+    Ok((req, kind))
+}
+
+/// Converts a response into the response format of the event kind.
+///
+/// The body is base64 encoded if it's not a valid UTF-8 string or the response has a `Content-Encoding` header.
+pub async fn from_response<B>(res: Response<B>, kind: EventKind) -> crate::Result<Value>
+where
+    B: HttpBody,
+    B::Error: Into<RouteError>,
+{
+    let (parts, body) = res.into_parts();
+    let body = body::to_bytes(body).await.map_err(Into::into)?;
+
+    let is_encoded = parts.headers.contains_key(CONTENT_ENCODING);
+    let (body, is_base64_encoded) = match std::str::from_utf8(&body) {
+        Ok(text) if !is_encoded => (text.to_owned(), false),
+        _ => (BASE64.encode(&body), true),
+    };
+
+    let mut value = json!({
+        "statusCode": parts.status.as_u16(),
+        "body": body,
+        "isBase64Encoded": is_base64_encoded,
+    });
+
+    match kind {
+        EventKind::ApiGatewayV1
+        | EventKind::Alb {
+            multi_value_headers: true,
+        } => {
+            value["multiValueHeaders"] = multi_value_headers(&parts.headers);
+        }
+        EventKind::Alb {
+            multi_value_headers: false,
+        } => {
+            value["headers"] = single_value_headers(&parts.headers, false);
+        }
+        EventKind::ApiGatewayV2 => {
+            // The HTTP API joins the repeated headers, except the cookies which have their own field.
+            value["headers"] = single_value_headers(&parts.headers, true);
+            value["cookies"] = parts
+                .headers
+                .get_all(SET_COOKIE)
+                .iter()
+                .filter_map(|val| val.to_str().ok())
+                .map(Value::from)
+                .collect();
+        }
+    }
+
+    if let EventKind::Alb { .. } = kind {
+        value["statusDescription"] = Value::from(format!(
+            "{} {}",
+            parts.status.as_u16(),
+            parts.status.canonical_reason().unwrap_or_default()
+        ));
+    }
+
+    Ok(value)
+}
+
+fn event_kind(event: &Value) -> crate::Result<EventKind> {
+    let context = &event["requestContext"];
+
+    if context.get("elb").is_some() {
+        Ok(EventKind::Alb {
+            multi_value_headers: event.get("multiValueHeaders").is_some(),
+        })
+    } else if event["version"] == "2.0" && context.get("http").is_some() {
+        Ok(EventKind::ApiGatewayV2)
+    } else if event.get("httpMethod").is_some() && context.is_object() {
+        Ok(EventKind::ApiGatewayV1)
+    } else {
+        Err(
+            Error::new("Unsupported Lambda event, expected an API Gateway or an Application Load Balancer event")
+                .into(),
+        )
+    }
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> crate::Result<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| Error::new(format!("The Lambda event doesn't have the `{}` field", field)).into())
+}
+
+// Prefers the multi-value query parameters, as the single-value ones only keep the last value of a parameter.
+fn query_params(event: &Value) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+    if let Some(params) = event["multiValueQueryStringParameters"].as_object() {
+        Box::new(params.iter().flat_map(|(key, vals)| {
+            vals.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(move |val| (key.as_str(), val))
+        }))
+    } else if let Some(params) = event["queryStringParameters"].as_object() {
+        Box::new(
+            params
+                .iter()
+                .filter_map(|(key, val)| val.as_str().map(|val| (key.as_str(), val))),
+        )
+    } else {
+        Box::new(std::iter::empty())
+    }
+}
+
+fn encode_query_params(event: &Value) -> String {
+    query_params(event)
+        .map(|(key, val)| {
+            format!(
+                "{}={}",
+                utf8_percent_encode(key, QUERY_COMPONENT),
+                utf8_percent_encode(val, QUERY_COMPONENT)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn read_headers(event: &Value, headers: &mut HeaderMap) -> crate::Result<()> {
+    if let Some(multi_value_headers) = event["multiValueHeaders"].as_object() {
+        for (name, vals) in multi_value_headers {
+            let name = HeaderName::from_bytes(name.as_bytes())?;
+            for val in vals.as_array().into_iter().flatten().filter_map(Value::as_str) {
+                headers.append(name.clone(), HeaderValue::from_str(val)?);
+            }
+        }
+    } else if let Some(single_value_headers) = event["headers"].as_object() {
+        for (name, val) in single_value_headers {
+            if let Some(val) = val.as_str() {
+                headers.append(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(val)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn remote_addr(event: &Value, kind: EventKind, headers: &HeaderMap) -> PeerAddr {
+    let context = &event["requestContext"];
+
+    let source_ip = match kind {
+        EventKind::ApiGatewayV1 => context["identity"]["sourceIp"].as_str(),
+        EventKind::ApiGatewayV2 => context["http"]["sourceIp"].as_str(),
+        // The load balancer appends the address of its peer to the `X-Forwarded-For` header.
+        EventKind::Alb { .. } => headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .next_back()
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.rsplit(',').next())
+            .map(str::trim),
+    };
+
+    source_ip
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .map(|ip| PeerAddr::Tcp(SocketAddr::new(ip, 0)))
+        .unwrap_or(PeerAddr::Unknown)
+}
+
+fn multi_value_headers(headers: &HeaderMap) -> Value {
+    let mut map = Map::new();
+
+    for name in headers.keys() {
+        let vals = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .map(Value::from)
+            .collect();
+        map.insert(name.as_str().to_owned(), Value::Array(vals));
+    }
+
+    Value::Object(map)
+}
+
+// The repeated headers are joined, except the `Set-Cookie` header which can't be joined, so only its last value is kept
+// unless the cookies are skipped.
+fn single_value_headers(headers: &HeaderMap, skip_cookies: bool) -> Value {
+    let mut map = Map::new();
+
+    for name in headers.keys() {
+        let mut vals = headers.get_all(name).iter().filter_map(|val| val.to_str().ok());
+
+        let val = if name != SET_COOKIE {
+            vals.collect::<Vec<&str>>().join(", ")
+        } else if skip_cookies {
+            continue;
+        } else {
+            match vals.next_back() {
+                Some(val) => val.to_owned(),
+                None => continue,
+            }
+        };
+
+        map.insert(name.as_str().to_owned(), Value::from(val));
+    }
+
+    Value::Object(map)
+}
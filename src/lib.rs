@@ -148,12 +148,32 @@
 //!
 //! ```
 //! use routerify::Router;
-//! use hyper::{Response, Body};
+//! use hyper::{Request, Response, Body};
 //! # use std::convert::Infallible;
 //!
 //! # fn run() -> Router<Body, Infallible> {
 //! let router = Router::builder()
-//!     .get("/about", |req| async move { Ok(Response::new(Body::from("About page"))) })
+//!     .get("/about", |req: Request<Body>| async move { Ok(Response::new(Body::from("About page"))) })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
+//! The type of a closure argument has to be annotated, as a handler can also take the
+//! [extractors](./extract/index.html) as its arguments, or no argument at all. And it can return any
+//! [`IntoResponse`](./trait.IntoResponse.html) type instead of a `Response<B>`:
+//!
+//! ```
+//! use routerify::Router;
+//! use hyper::{Body, StatusCode};
+//! # use std::convert::Infallible;
+//!
+//! # fn run() -> Router<Body, Infallible> {
+//! let router = Router::builder()
+//!     .get("/about", || async move { Ok("About page") })
+//!     .delete("/about", || async move { Ok(StatusCode::METHOD_NOT_ALLOWED) })
 //!     .build()
 //!     .unwrap();
 //! # router
@@ -173,12 +193,12 @@
 //!
 //! ```
 //! use routerify::Router;
-//! use hyper::{Response, Body};
+//! use hyper::{Request, Response, Body};
 //! # use std::convert::Infallible;
 //!
 //! # fn run() -> Router<Body, Infallible> {
 //! let router = Router::builder()
-//!     .get("/about", |req| async move { Ok(Response::new(Body::from("About page"))) })
+//!     .get("/about", |req: Request<Body>| async move { Ok(Response::new(Body::from("About page"))) })
 //!     .build()
 //!     .unwrap();
 //! # router
@@ -190,12 +210,12 @@
 //!
 //! ```
 //! use routerify::Router;
-//! use hyper::{Response, Body};
+//! use hyper::{Request, Response, Body};
 //! # use std::convert::Infallible;
 //!
 //! # fn run() -> Router<Body, Infallible> {
 //! let router = Router::builder()
-//!     .get("/users/*", |req| async move { Ok(Response::new(Body::from("It will match /users/, /users/any_path"))) })
+//!     .get("/users/*", |req: Request<Body>| async move { Ok(Response::new(Body::from("It will match /users/, /users/any_path"))) })
 //!     .build()
 //!     .unwrap();
 //! # router
@@ -209,14 +229,14 @@
 //!
 //! ```
 //! use routerify::Router;
-//! use hyper::{Response, Body, StatusCode};
+//! use hyper::{Request, Response, Body, StatusCode};
 //! # use std::convert::Infallible;
 //!
 //! # fn run() -> Router<Body, Infallible> {
 //! let router = Router::builder()
-//!     .get("/users", |req| async move { Ok(Response::new(Body::from("User List"))) })
+//!     .get("/users", |req: Request<Body>| async move { Ok(Response::new(Body::from("User List"))) })
 //!     // It fallbacks to the following route for any non-existent routes.
-//!     .any(|_req| async move {
+//!     .any(|| async move {
 //!         Ok(
 //!             Response::builder()
 //!             .status(StatusCode::NOT_FOUND)
@@ -248,12 +268,12 @@
 //! use routerify::Router;
 //! // Add routerify prelude traits.
 //! use routerify::prelude::*;
-//! use hyper::{Response, Body};
+//! use hyper::{Request, Response, Body};
 //! # use std::convert::Infallible;
 //!
 //! # fn run() -> Router<Body, Infallible> {
 //! let router = Router::builder()
-//!     .get("/users/:userName/books/:bookName", |req| async move {
+//!     .get("/users/:userName/books/:bookName", |req: Request<Body>| async move {
 //!         let user_name = req.param("userName").unwrap();
 //!         let book_name = req.param("bookName").unwrap();
 //!
@@ -276,13 +296,13 @@
 //! ```
 //! use routerify::Router;
 //! use routerify::prelude::*;
-//! use hyper::{Response, Body};
+//! use hyper::{Request, Response, Body};
 //! use std::convert::Infallible;
 //!
 //! fn api_router() -> Router<Body, Infallible> {
 //!     Router::builder()
-//!         .get("/books", |req| async move { Ok(Response::new(Body::from("List of books"))) })
-//!         .get("/books/:bookId", |req| async move {
+//!         .get("/books", |req: Request<Body>| async move { Ok(Response::new(Body::from("List of books"))) })
+//!         .get("/books/:bookId", |req: Request<Body>| async move {
 //!             Ok(Response::new(Body::from(format!("Show book: {}", req.param("bookId").unwrap()))))
 //!          })
 //!         .build()
@@ -696,9 +716,18 @@
 //!
 //! ## Error Handling
 //!
-//! Any route or middleware could go wrong and throw an error. `Routerify` tries to add a default error handler in some cases. But, it also
+//! Any route or middleware could go wrong and throw an error. `Routerify` adds a default error handler which responds with
+//! `500 Internal Server Error` and the error message. But, it also
 //! allows to attach a custom error handler. The error handler generates a response based on the error and the request info (optional).
 //!
+//! The default error handler responds to the [rejections](./extract/struct.Rejection.html), the invalid
+//! [route parameters](./enum.ParamError.html) and [query strings](./struct.QueryError.html) with their own status and
+//! message instead.
+//!
+//! The default error handler, the default `404 Not Found` route and the global `OPTIONS` route are added for any
+//! response body type implementing the [`DefaultBody`](./trait.DefaultBody.html) trait, i.e. the ones which can be built
+//! from a `&'static str` and a `String` like `hyper::Body`, `String`, `Vec<u8>` and `Bytes`.
+//!
 //! Routes and middleware may return any error type. The type must be the same for all routes, middleware and a router instance.
 //! The error is boxed into [`RouteError`](./type.RouteError.html)
 //! and propagated into an error handler. There, the original error is accessible after downcasting.
@@ -710,7 +739,7 @@
 //! ```
 //! use routerify::{Router, Middleware};
 //! use routerify::prelude::*;
-//! use hyper::{Response, Body, StatusCode};
+//! use hyper::{Request, Response, Body, StatusCode};
 //!
 //! // The error handler will accept the thrown error in routerify::Error type and
 //! // it will have to generate a response based on the error.
@@ -723,7 +752,7 @@
 //!
 //! # fn run() -> Router<Body, hyper::Error> {
 //! let router = Router::builder()
-//!      .get("/users", |req| async move { Ok(Response::new(Body::from("It might raise an error"))) })
+//!      .get("/users", |req: Request<Body>| async move { Ok(Response::new(Body::from("It might raise an error"))) })
 //!      // Here attach the custom error handler defined above.
 //!      .err_handler(error_handler)
 //!      .build()
@@ -741,7 +770,7 @@
 //! ```
 //! use routerify::{Router, Middleware, RequestInfo};
 //! use routerify::prelude::*;
-//! use hyper::{Response, Body, StatusCode};
+//! use hyper::{Request, Response, Body, StatusCode};
 //!
 //! // The error handler will accept the thrown error and the request info and
 //! // it will generate a response.
@@ -755,7 +784,7 @@
 //!
 //! # fn run() -> Router<Body, hyper::Error> {
 //! let router = Router::builder()
-//!      .get("/users", |req| async move { Ok(Response::new(Body::from("It might raise an error"))) })
+//!      .get("/users", |req: Request<Body>| async move { Ok(Response::new(Body::from("It might raise an error"))) })
 //!      // Now register this error handler.
 //!      .err_handler_with_info(error_handler)
 //!      .build()
@@ -767,26 +796,60 @@
 
 pub use self::error::{Error, RouteError};
 pub use self::middleware::{Middleware, PostMiddleware, PreMiddleware};
+pub use self::response::{DefaultBody, IntoResponse};
 pub use self::route::Route;
 pub use self::router::{Router, RouterBuilder};
+pub use self::server::{serve, Listener, Server};
+pub use self::service::Connection;
+pub use self::service::InFlightBody;
 #[doc(hidden)]
 pub use self::service::RequestService;
 pub use self::service::RequestServiceBuilder;
 pub use self::service::RouterService;
-pub use self::types::{RequestInfo, RouteParams};
+pub use self::types::{
+    ClientInfo, ConnectionInfo, ForwardedHeader, ParamError, PeerAddr, QueryError, QueryParams, RequestInfo,
+    RouteParams, Shutdown,
+};
 
+#[cfg(feature = "cgi")]
+pub mod cgi;
+#[cfg(any(
+    feature = "compression-gzip",
+    feature = "compression-deflate",
+    feature = "compression-br"
+))]
+pub mod compression;
 mod constants;
 mod data_map;
 mod error;
 pub mod ext;
+pub mod extract;
+#[cfg(feature = "fs")]
+pub mod fs;
 mod helpers;
+#[cfg(feature = "hyper1")]
+pub mod hyper1;
+#[cfg(feature = "lambda")]
+pub mod lambda;
 mod middleware;
 pub mod prelude;
+mod proxy;
+pub mod proxy_protocol;
 mod regex_generator;
+mod response;
 mod route;
 mod router;
+mod server;
 mod service;
+#[cfg(feature = "sse")]
+pub mod sse;
+#[cfg(feature = "test-util")]
+pub mod test;
+#[cfg(feature = "tower")]
+pub mod tower;
 mod types;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 /// A Result type often returned from methods that can have routerify errors.
 pub type Result<T> = std::result::Result<T, RouteError>;
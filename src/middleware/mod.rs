@@ -10,23 +10,28 @@ mod pre;
 
 /// Enum type for all the middleware types. Please refer to the [Middleware](./index.html#middleware) for more info.
 ///
-/// This `Middleware<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Middleware<B, E, ReqBody>` type accepts three type parameters: `B`, `E` and `ReqBody`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
 #[derive(Debug)]
-pub enum Middleware<B, E> {
+pub enum Middleware<B, E, ReqBody = hyper::Body> {
     /// Variant for the pre middleware. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
-    Pre(PreMiddleware<E>),
+    Pre(PreMiddleware<E, ReqBody>),
 
     /// Variant for the post middleware. Refer to [Post Middleware](./index.html#post-middleware) for more info.
     Post(PostMiddleware<B, E>),
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    Middleware<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Middleware<B, E, ReqBody>
 {
     /// Creates a pre middleware with a handler at the `/*` path.
     ///
@@ -46,10 +51,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn pre<H, R>(handler: H) -> Middleware<B, E>
+    pub fn pre<H, R>(handler: H) -> Middleware<B, E, ReqBody>
     where
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqBody>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqBody>, E>> + Send + 'static,
     {
         Middleware::pre_with_path("/*", handler).unwrap()
     }
@@ -72,7 +77,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn post<H, R>(handler: H) -> Middleware<B, E>
+    pub fn post<H, R>(handler: H) -> Middleware<B, E, ReqBody>
     where
         H: Fn(Response<B>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
@@ -107,7 +112,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_info<H, R>(handler: H) -> Middleware<B, E>
+    pub fn post_with_info<H, R>(handler: H) -> Middleware<B, E, ReqBody>
     where
         H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
@@ -133,11 +138,11 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqBody>>
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqBody>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqBody>, E>> + Send + 'static,
     {
         Ok(Middleware::Pre(PreMiddleware::new(path, handler)?))
     }
@@ -160,7 +165,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqBody>>
     where
         P: Into<String>,
         H: Fn(Response<B>) -> R + Send + Sync + 'static,
@@ -196,7 +201,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqBody>>
     where
         P: Into<String>,
         H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
@@ -6,30 +6,32 @@ use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
 
-type Handler<E> = Box<dyn Fn(Request<hyper::Body>) -> HandlerReturn<E> + Send + Sync + 'static>;
-type HandlerReturn<E> = Box<dyn Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static>;
+type Handler<E, ReqBody> = Box<dyn Fn(Request<ReqBody>) -> HandlerReturn<E, ReqBody> + Send + Sync + 'static>;
+type HandlerReturn<E, ReqBody> = Box<dyn Future<Output = Result<Request<ReqBody>, E>> + Send + 'static>;
 
 /// The pre middleware type. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
 ///
-/// This `PreMiddleware<E>` type accepts a single type parameter: `E`.
+/// This `PreMiddleware<E, ReqBody>` type accepts two type parameters: `E` and `ReqBody`.
 ///
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
-pub struct PreMiddleware<E> {
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
+pub struct PreMiddleware<E, ReqBody = hyper::Body> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<E>>,
+    pub(crate) handler: Option<Handler<E, ReqBody>>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
 }
 
-impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<E> {
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static, ReqBody: Send + 'static> PreMiddleware<E, ReqBody> {
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
-        handler: Handler<E>,
+        handler: Handler<E, ReqBody>,
         scope_depth: u32,
-    ) -> crate::Result<PreMiddleware<E>> {
+    ) -> crate::Result<PreMiddleware<E, ReqBody>> {
         let path = path.into();
         let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
             Error::new(format!(
@@ -64,17 +66,17 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
     /// # }
     /// # run();
     /// ```
-    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E>>
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E, ReqBody>>
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqBody>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqBody>, E>> + Send + 'static,
     {
-        let handler: Handler<E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        let handler: Handler<E, ReqBody> = Box::new(move |req: Request<ReqBody>| Box::new(handler(req)));
         PreMiddleware::new_with_boxed_handler(path, handler, 1)
     }
 
-    pub(crate) async fn process(&self, req: Request<hyper::Body>) -> crate::Result<Request<hyper::Body>> {
+    pub(crate) async fn process(&self, req: Request<ReqBody>) -> crate::Result<Request<ReqBody>> {
         let handler = self
             .handler
             .as_ref()
@@ -84,7 +86,7 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
     }
 }
 
-impl<E> Debug for PreMiddleware<E> {
+impl<E, ReqBody> Debug for PreMiddleware<E, ReqBody> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
     }
@@ -0,0 +1,411 @@
+use crate::types::{ClientInfo, ForwardedHeader, PeerAddr};
+use crate::Error;
+use hyper::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+const X_FORWARDED_HOST: &str = "x-forwarded-host";
+const X_REAL_IP: &str = "x-real-ip";
+
+// The list of the proxies whose forwarding headers are trusted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrustedProxies {
+    ranges: Vec<IpRange>,
+    // The only header which is trusted, all of them are looked up in order if it's not set.
+    pub(crate) header: Option<ForwardedHeader>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+// A hop of the forwarding chain, the leftmost hop is the closest one to the client.
+#[derive(Debug, Default)]
+struct Hop {
+    addr: Option<IpPort>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+type IpPort = (IpAddr, u16);
+
+impl TrustedProxies {
+    pub(crate) fn parse<I, S>(ranges: I) -> crate::Result<TrustedProxies>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let ranges = ranges
+            .into_iter()
+            .map(|range| IpRange::parse(range.as_ref()))
+            .collect::<crate::Result<Vec<IpRange>>>()?;
+
+        Ok(TrustedProxies { ranges, header: None })
+    }
+
+    pub(crate) fn contains(&self, ip: IpAddr) -> bool {
+        let ip = canonical_ip(ip);
+        self.ranges.iter().any(|range| range.contains(ip))
+    }
+
+    pub(crate) fn resolve(&self, peer_addr: &PeerAddr, headers: &HeaderMap) -> ClientInfo {
+        let trusted = match peer_addr {
+            PeerAddr::Tcp(addr) => self.contains(addr.ip()),
+            _ => false,
+        };
+
+        if !trusted {
+            return ClientInfo::new(peer_addr.clone());
+        }
+
+        let hops = match self.header {
+            Some(ForwardedHeader::Forwarded) => forwarded_hops(headers),
+            Some(ForwardedHeader::XForwardedFor) => x_forwarded_hops(headers),
+            Some(ForwardedHeader::XRealIp) => x_real_ip_hops(headers),
+            None => forwarded_hops(headers)
+                .or_else(|| x_forwarded_hops(headers))
+                .or_else(|| x_real_ip_hops(headers)),
+        };
+
+        match hops {
+            Some(hops) => {
+                let hop = self.select_client_hop(hops);
+                let addr = hop
+                    .addr
+                    .map(|(ip, port)| PeerAddr::Tcp(SocketAddr::new(ip, port)))
+                    .unwrap_or(PeerAddr::Unknown);
+
+                ClientInfo::new(addr).with_scheme(hop.proto).with_host(hop.host)
+            }
+            None => ClientInfo::new(peer_addr.clone()),
+        }
+    }
+
+    // Walks the chain from the right and returns the first hop which is not a trusted proxy. If all of them are trusted,
+    // the leftmost one is the client.
+    fn select_client_hop(&self, mut hops: Vec<Hop>) -> Hop {
+        while hops.len() > 1 {
+            let hop = hops.pop().unwrap();
+            match hop.addr {
+                Some((ip, _)) if self.contains(ip) => continue,
+                _ => return hop,
+            }
+        }
+
+        hops.pop().unwrap()
+    }
+}
+
+impl IpRange {
+    fn parse(range: &str) -> crate::Result<IpRange> {
+        let range = range.trim();
+        let err = || Error::new(format!("Couldn't parse the trusted proxy range: {}", range));
+
+        let (ip, prefix_len) = match range.split_once('/') {
+            Some((ip, prefix_len)) => (ip, Some(prefix_len)),
+            None => (range, None),
+        };
+
+        let network = canonical_ip(ip.parse::<IpAddr>().map_err(|_| err())?);
+        let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse::<u8>().map_err(|_| err())?,
+            None => max_prefix_len,
+        };
+
+        if prefix_len > max_prefix_len {
+            return Err(err().into());
+        }
+
+        Ok(IpRange { network, prefix_len })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+// Treats the IPv4-mapped IPv6 addresses e.g. `::ffff:10.0.0.1` as IPv4 addresses.
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+// A header which is present but malformed yields a single unknown hop, so the client info is unknown instead of being
+// resolved from the next header, which could have been passed through from the client.
+fn malformed_hops() -> Option<Vec<Hop>> {
+    Some(vec![Hop::default()])
+}
+
+// Parses the `Forwarded` header as defined in RFC 7239.
+fn forwarded_hops(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let mut hops = Vec::new();
+
+    for value in headers.get_all(hyper::header::FORWARDED).iter() {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => return malformed_hops(),
+        };
+
+        for element in split_unquoted(value, ',') {
+            let mut hop = Hop::default();
+
+            for pair in split_unquoted(element, ';') {
+                let (key, val) = match pair.split_once('=') {
+                    Some(pair) => pair,
+                    None => continue,
+                };
+                let val = unquote(val.trim());
+
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "for" => hop.addr = parse_node(val),
+                    "proto" => hop.proto = Some(val.to_ascii_lowercase()),
+                    "host" => hop.host = Some(val.to_owned()),
+                    _ => {}
+                }
+            }
+
+            hops.push(hop);
+        }
+    }
+
+    if hops.is_empty() {
+        None
+    } else {
+        Some(hops)
+    }
+}
+
+fn x_forwarded_hops(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let nodes = list_values(headers, X_FORWARDED_FOR)?;
+    let nodes = match nodes {
+        Some(nodes) => nodes,
+        None => return malformed_hops(),
+    };
+
+    // Every proxy appends to the lists, so the proto and the host of a hop are aligned from the right with its address.
+    // The leftmost entries could have been sent by the client, a list shorter than the chain is only trusted for its
+    // rightmost entry which is set by the closest proxy.
+    let protos = list_values(headers, X_FORWARDED_PROTO).flatten().unwrap_or_default();
+    let hosts = list_values(headers, X_FORWARDED_HOST).flatten().unwrap_or_default();
+
+    let hops = nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| Hop {
+            addr: parse_node(node),
+            proto: aligned_value(&protos, idx, nodes.len()).map(|proto| proto.to_ascii_lowercase()),
+            host: aligned_value(&hosts, idx, nodes.len()).map(ToOwned::to_owned),
+        })
+        .collect();
+
+    Some(hops)
+}
+
+fn x_real_ip_hops(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let value = match headers.get(X_REAL_IP)?.to_str() {
+        Ok(value) => value,
+        Err(_) => return malformed_hops(),
+    };
+
+    Some(vec![Hop {
+        addr: parse_node(value.trim()),
+        ..Hop::default()
+    }])
+}
+
+// Collects the entries of a comma separated header over all its values. It's `None` if the header is missing, and
+// `Some(None)` if it's not valid UTF-8.
+fn list_values<'a>(headers: &'a HeaderMap, name: &str) -> Option<Option<Vec<&'a str>>> {
+    let mut values = headers.get_all(name).iter().peekable();
+    values.peek()?;
+
+    let entries = values
+        .map(|value| value.to_str().ok())
+        .collect::<Option<Vec<&str>>>()
+        .map(|values| {
+            values
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .collect()
+        });
+
+    Some(entries)
+}
+
+fn aligned_value<'a>(values: &[&'a str], idx: usize, len: usize) -> Option<&'a str> {
+    let aligned = (idx + values.len()).checked_sub(len).and_then(|idx| values.get(idx));
+    aligned.or_else(|| values.last()).copied().filter(|val| !val.is_empty())
+}
+
+// Parses a node e.g. `192.0.2.43`, `192.0.2.43:4711`, `[2001:db8::1]:4711` or `2001:db8::1`. The `unknown` and the
+// obfuscated identifiers can't be parsed.
+fn parse_node(node: &str) -> Option<IpPort> {
+    if let Some(rest) = node.strip_prefix('[') {
+        let (ip, port) = rest.split_once(']')?;
+        let ip = ip.parse::<IpAddr>().ok()?;
+        let port = match port.strip_prefix(':') {
+            Some(port) => port.parse::<u16>().unwrap_or(0),
+            None => 0,
+        };
+        return Some((canonical_ip(ip), port));
+    }
+
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some((canonical_ip(addr.ip()), addr.port()));
+    }
+
+    node.parse::<IpAddr>().ok().map(|ip| (canonical_ip(ip), 0))
+}
+
+fn split_unquoted(value: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (idx, ch) in value.char_indices() {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        } else if ch == sep && !in_quotes {
+            parts.push(value[start..idx].trim());
+            start = idx + 1;
+        }
+    }
+    parts.push(value[start..].trim());
+
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|val| val.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn resolve(peer: &str, headers: &[(&'static str, &str)]) -> ClientInfo {
+        let proxies = TrustedProxies::parse(vec!["10.0.0.0/8", "fd00::/8"]).unwrap();
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, value.parse().unwrap());
+        }
+        proxies.resolve(&PeerAddr::Tcp(peer.parse().unwrap()), &map)
+    }
+
+    #[test]
+    fn test_parse_trusted_proxies() {
+        assert!(TrustedProxies::parse(vec!["10.0.0.0/8", "192.168.1.1", "::1/128"]).is_ok());
+        assert!(TrustedProxies::parse(vec!["10.0.0.0/33"]).is_err());
+        assert!(TrustedProxies::parse(vec!["localhost"]).is_err());
+
+        let proxies = TrustedProxies::parse(vec!["10.1.0.0/16"]).unwrap();
+        assert!(proxies.contains("10.1.2.3".parse().unwrap()));
+        assert!(proxies.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!proxies.contains("10.2.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_resolve_client_info() {
+        let info = resolve("1.2.3.4:80", &[("x-forwarded-for", "5.6.7.8")]);
+        assert_eq!(info.addr(), &PeerAddr::Tcp("1.2.3.4:80".parse().unwrap()));
+
+        let info = resolve(
+            "10.0.0.1:80",
+            &[(
+                "forwarded",
+                "for=\"[2001:db8::1]:4711\";proto=HTTPS;host=example.com, for=10.0.0.2",
+            )],
+        );
+        assert_eq!(info.addr(), &PeerAddr::Tcp("[2001:db8::1]:4711".parse().unwrap()));
+        assert_eq!(info.scheme(), Some("https"));
+        assert_eq!(info.host(), Some("example.com"));
+
+        let info = resolve(
+            "10.0.0.1:80",
+            &[
+                ("x-forwarded-for", "6.6.6.6, 5.6.7.8, 10.0.0.2"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-host", "example.com"),
+            ],
+        );
+        assert_eq!(info.addr(), &PeerAddr::Tcp("5.6.7.8:0".parse().unwrap()));
+        assert_eq!(info.scheme(), Some("https"));
+        assert_eq!(info.host(), Some("example.com"));
+
+        // The client sent its own values before the trusted proxy appended the real ones.
+        let info = resolve(
+            "10.0.0.1:80",
+            &[
+                ("x-forwarded-for", "6.6.6.6, 5.6.7.8"),
+                ("x-forwarded-proto", "https, http"),
+                ("x-forwarded-host", "evil.com, example.com"),
+            ],
+        );
+        assert_eq!(info.addr(), &PeerAddr::Tcp("5.6.7.8:0".parse().unwrap()));
+        assert_eq!(info.scheme(), Some("http"));
+        assert_eq!(info.host(), Some("example.com"));
+
+        let info = resolve(
+            "10.0.0.1:80",
+            &[
+                ("x-forwarded-for", "5.6.7.8"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-proto", "http"),
+            ],
+        );
+        assert_eq!(info.scheme(), Some("http"));
+
+        let info = resolve("10.0.0.1:80", &[("x-real-ip", "5.6.7.8")]);
+        assert_eq!(info.addr(), &PeerAddr::Tcp("5.6.7.8:0".parse().unwrap()));
+
+        let info = resolve("10.0.0.1:80", &[("forwarded", "for=unknown")]);
+        assert_eq!(info.addr(), &PeerAddr::Unknown);
+
+        let info = resolve("10.0.0.1:80", &[]);
+        assert_eq!(info.addr(), &PeerAddr::Tcp("10.0.0.1:80".parse().unwrap()));
+        assert_eq!(info.scheme(), None);
+    }
+
+    #[test]
+    fn test_resolve_with_forwarded_header() {
+        let mut proxies = TrustedProxies::parse(vec!["10.0.0.0/8"]).unwrap();
+        proxies.header = Some(ForwardedHeader::XForwardedFor);
+
+        // The `Forwarded` header was passed through from the client.
+        let mut headers = HeaderMap::new();
+        headers.insert("forwarded", "for=1.2.3.4".parse().unwrap());
+        headers.insert("x-forwarded-for", "5.6.7.8".parse().unwrap());
+        let info = proxies.resolve(&PeerAddr::Tcp("10.0.0.1:80".parse().unwrap()), &headers);
+        assert_eq!(info.addr(), &PeerAddr::Tcp("5.6.7.8:0".parse().unwrap()));
+
+        // A malformed trusted header doesn't fall back to the other headers.
+        let mut headers = HeaderMap::new();
+        headers.insert("forwarded", HeaderValue::from_bytes(b"for=\xff").unwrap());
+        headers.insert("x-forwarded-for", "5.6.7.8".parse().unwrap());
+        proxies.header = None;
+        let info = proxies.resolve(&PeerAddr::Tcp("10.0.0.1:80".parse().unwrap()), &headers);
+        assert_eq!(info.addr(), &PeerAddr::Unknown);
+        assert_eq!(info.scheme(), None);
+    }
+}
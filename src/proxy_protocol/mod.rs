@@ -0,0 +1,375 @@
+//! Support for the HAProxy [PROXY protocol](https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt) v1 and v2.
+//!
+//! A load balancer speaking the PROXY protocol sends a header with the original source and destination addresses
+//! before the HTTP traffic. The [`accept`](./fn.accept.html) function reads and validates that header and returns a
+//! [`ProxyStream`](./struct.ProxyStream.html) which implements the [`Connection`](../trait.Connection.html) trait with
+//! the original addresses, so [`RequestExt::remote_addr`](../ext/trait.RequestExt.html#method.remote_addr) returns the
+//! address of the real client.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use hyper::{server::conn::Http, service::Service, Body, Response};
+//! use routerify::{proxy_protocol, Router, RouterService};
+//! use std::convert::Infallible;
+//! use tokio::net::TcpListener;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router: Router<Body, Infallible> = Router::builder()
+//!         .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+//!         .build()
+//!         .unwrap();
+//!     let mut router_service = RouterService::new(router).unwrap();
+//!
+//!     let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+//!     loop {
+//!         let (stream, _) = listener.accept().await.unwrap();
+//!         let stream = match proxy_protocol::accept(stream).await {
+//!             Ok(stream) => stream,
+//!             Err(err) => {
+//!                 eprintln!("Invalid PROXY protocol header: {}", err);
+//!                 continue;
+//!             }
+//!         };
+//!
+//!         let service = router_service.call(&stream).await.unwrap();
+//!         tokio::spawn(Http::new().serve_connection(stream, service));
+//!     }
+//! }
+//! ```
+
+use crate::service::Connection;
+use crate::types::{ConnectionInfo, PeerAddr};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LEN: usize = 16;
+
+/// Reads the PROXY protocol header from the provided stream and returns the stream carrying the original addresses.
+///
+/// Both the v1 and the v2 headers are accepted. An [`InvalidData`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData)
+/// error is returned if the header is malformed or missing.
+pub async fn accept<IO: AsyncRead + Unpin>(mut io: IO) -> io::Result<ProxyStream<IO>> {
+    let mut buf = Vec::with_capacity(V2_HEADER_LEN);
+
+    loop {
+        if let Some((header_len, source, destination)) = parse_header(&buf)? {
+            return Ok(ProxyStream {
+                io,
+                buf: buf.split_off(header_len),
+                pos: 0,
+                source,
+                destination,
+            });
+        }
+
+        let mut chunk = [0; 256];
+        let n = io.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed before the PROXY protocol header",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// A stream which was prefixed with a PROXY protocol header.
+///
+/// It's created by the [`accept`](./fn.accept.html) function. The source address is used as the peer address of the
+/// connection and the destination address as the local one. For the `LOCAL` command and the unknown or the unix
+/// address families, the addresses of the underlying connection are used.
+#[derive(Debug)]
+pub struct ProxyStream<IO> {
+    io: IO,
+    // The bytes which were read after the header.
+    buf: Vec<u8>,
+    pos: usize,
+    source: Option<SocketAddr>,
+    destination: Option<SocketAddr>,
+}
+
+impl<IO> ProxyStream<IO> {
+    /// Returns the original source address sent by the proxy.
+    pub fn source(&self) -> Option<SocketAddr> {
+        self.source
+    }
+
+    /// Returns the original destination address sent by the proxy.
+    pub fn destination(&self) -> Option<SocketAddr> {
+        self.destination
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &IO {
+        &self.io
+    }
+}
+
+impl<IO: Connection> Connection for ProxyStream<IO> {
+    fn peer(&self) -> PeerAddr {
+        self.source.map(PeerAddr::Tcp).unwrap_or_else(|| self.io.peer())
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        let local_addr = match self.destination {
+            Some(destination) => PeerAddr::Tcp(destination),
+            None => self.io.connection_info().local_addr().clone(),
+        };
+
+        ConnectionInfo::new(self.peer()).with_local_addr(local_addr)
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for ProxyStream<IO> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        if self.pos < self.buf.len() {
+            let n = (self.buf.len() - self.pos).min(buf.remaining());
+            buf.put_slice(&self.buf[self.pos..self.pos + n]);
+            self.pos += n;
+            return Poll::Ready(Ok(()));
+        }
+
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for ProxyStream<IO> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+type ParsedHeader = (usize, Option<SocketAddr>, Option<SocketAddr>);
+
+// Returns `None` if more bytes are needed to parse the header.
+fn parse_header(buf: &[u8]) -> io::Result<Option<ParsedHeader>> {
+    if buf.len() >= V2_SIGNATURE.len() && buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else if buf.len() >= V1_PREFIX.len() && buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if V2_SIGNATURE.starts_with(buf) || V1_PREFIX.starts_with(buf) {
+        Ok(None)
+    } else {
+        Err(invalid("Missing PROXY protocol header"))
+    }
+}
+
+fn parse_v1(buf: &[u8]) -> io::Result<Option<ParsedHeader>> {
+    let end = match buf.windows(2).position(|w| w == b"\r\n") {
+        Some(end) => end,
+        None if buf.len() >= V1_MAX_LEN => return Err(invalid("PROXY protocol v1 header is too long")),
+        None => return Ok(None),
+    };
+
+    if end + 2 > V1_MAX_LEN {
+        return Err(invalid("PROXY protocol v1 header is too long"));
+    }
+
+    let line =
+        std::str::from_utf8(&buf[V1_PREFIX.len()..end]).map_err(|_| invalid("Invalid PROXY protocol v1 header"))?;
+    let parts = line.split(' ').collect::<Vec<&str>>();
+
+    let (source, destination) = match parts.as_slice() {
+        ["UNKNOWN", ..] => (None, None),
+        [proto @ "TCP4", src_ip, dst_ip, src_port, dst_port] | [proto @ "TCP6", src_ip, dst_ip, src_port, dst_port] => {
+            let parse_ip = |ip: &str| -> io::Result<IpAddr> {
+                let ip = if *proto == "TCP4" {
+                    ip.parse::<Ipv4Addr>().map(IpAddr::V4)
+                } else {
+                    ip.parse::<Ipv6Addr>().map(IpAddr::V6)
+                };
+                ip.map_err(|_| invalid("Invalid address in PROXY protocol v1 header"))
+            };
+            let parse_port = |port: &str| -> io::Result<u16> {
+                if port.len() > 1 && port.starts_with('0') {
+                    return Err(invalid("Invalid port in PROXY protocol v1 header"));
+                }
+                port.parse::<u16>()
+                    .map_err(|_| invalid("Invalid port in PROXY protocol v1 header"))
+            };
+
+            (
+                Some(SocketAddr::new(parse_ip(src_ip)?, parse_port(src_port)?)),
+                Some(SocketAddr::new(parse_ip(dst_ip)?, parse_port(dst_port)?)),
+            )
+        }
+        _ => return Err(invalid("Invalid PROXY protocol v1 header")),
+    };
+
+    Ok(Some((end + 2, source, destination)))
+}
+
+fn parse_v2(buf: &[u8]) -> io::Result<Option<ParsedHeader>> {
+    if buf.len() < V2_HEADER_LEN {
+        return Ok(None);
+    }
+
+    let version = buf[12] >> 4;
+    let command = buf[12] & 0x0F;
+    let family = buf[13];
+    let len = u16::from_be_bytes([buf[14], buf[15]]) as usize;
+
+    if version != 2 {
+        return Err(invalid("Unsupported PROXY protocol version"));
+    }
+
+    if command > 1 {
+        return Err(invalid("Invalid PROXY protocol v2 command"));
+    }
+
+    if buf.len() < V2_HEADER_LEN + len {
+        return Ok(None);
+    }
+
+    let addrs = &buf[V2_HEADER_LEN..V2_HEADER_LEN + len];
+    let header_len = V2_HEADER_LEN + len;
+
+    // The `LOCAL` command is used by the proxy for health checks, the connection's own addresses are kept.
+    if command == 0 {
+        return Ok(Some((header_len, None, None)));
+    }
+
+    let (source, destination) = match family >> 4 {
+        // AF_INET
+        0x1 => {
+            if addrs.len() < 12 {
+                return Err(invalid("Truncated PROXY protocol v2 addresses"));
+            }
+            let src_ip = Ipv4Addr::new(addrs[0], addrs[1], addrs[2], addrs[3]);
+            let dst_ip = Ipv4Addr::new(addrs[4], addrs[5], addrs[6], addrs[7]);
+            let src_port = u16::from_be_bytes([addrs[8], addrs[9]]);
+            let dst_port = u16::from_be_bytes([addrs[10], addrs[11]]);
+            (
+                Some(SocketAddr::new(IpAddr::V4(src_ip), src_port)),
+                Some(SocketAddr::new(IpAddr::V4(dst_ip), dst_port)),
+            )
+        }
+        // AF_INET6
+        0x2 => {
+            if addrs.len() < 36 {
+                return Err(invalid("Truncated PROXY protocol v2 addresses"));
+            }
+            let mut src_ip = [0; 16];
+            let mut dst_ip = [0; 16];
+            src_ip.copy_from_slice(&addrs[0..16]);
+            dst_ip.copy_from_slice(&addrs[16..32]);
+            let src_port = u16::from_be_bytes([addrs[32], addrs[33]]);
+            let dst_port = u16::from_be_bytes([addrs[34], addrs[35]]);
+            (
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(src_ip)), src_port)),
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(dst_ip)), dst_port)),
+            )
+        }
+        // AF_UNSPEC and AF_UNIX
+        0x0 | 0x3 => (None, None),
+        _ => return Err(invalid("Invalid PROXY protocol v2 address family")),
+    };
+
+    Ok(Some((header_len, source, destination)))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    async fn accept_bytes(bytes: &[u8]) -> io::Result<ProxyStream<tokio::io::DuplexStream>> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(bytes).await.unwrap();
+        drop(client);
+        accept(server).await
+    }
+
+    #[tokio::test]
+    async fn test_accept_v1() {
+        let mut stream = accept_bytes(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nGET / HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        assert_eq!(stream.source(), Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(stream.destination(), Some("198.51.100.1:443".parse().unwrap()));
+        assert_eq!(stream.peer(), PeerAddr::Tcp("192.0.2.1:56324".parse().unwrap()));
+
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "GET / HTTP/1.1\r\n");
+
+        let stream = accept_bytes(b"PROXY TCP6 2001:db8::1 2001:db8::2 4711 80\r\n")
+            .await
+            .unwrap();
+        assert_eq!(stream.source(), Some("[2001:db8::1]:4711".parse().unwrap()));
+
+        let stream = accept_bytes(b"PROXY UNKNOWN\r\n").await.unwrap();
+        assert_eq!(stream.peer(), PeerAddr::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_accept_v2() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[
+            0x21, 0x11, 0x00, 0x0C, 192, 0, 2, 1, 198, 51, 100, 1, 0xDC, 0x04, 0x01, 0xBB,
+        ]);
+        header.extend_from_slice(b"payload");
+
+        let mut stream = accept_bytes(&header).await.unwrap();
+        assert_eq!(stream.source(), Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(stream.destination(), Some("198.51.100.1:443".parse().unwrap()));
+        assert_eq!(
+            stream.connection_info().local_addr(),
+            &PeerAddr::Tcp("198.51.100.1:443".parse().unwrap())
+        );
+
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "payload");
+
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        let stream = accept_bytes(&local).await.unwrap();
+        assert_eq!(stream.source(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reject_malformed_header() {
+        let err = accept_bytes(b"GET / HTTP/1.1\r\n\r\n").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = accept_bytes(b"PROXY TCP4 192.0.2.1 198.51.100.1 99999 443\r\n")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = accept_bytes(b"PROXY TCP4 2001:db8::1 198.51.100.1 1 443\r\n")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x11, 0x00, 0x04, 192, 0, 2, 1]);
+        let err = accept_bytes(&header).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = accept_bytes(b"PROXY TCP4").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,239 @@
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue};
+use hyper::{Response, StatusCode};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+/// A type which can be converted into a response, so it can be returned from the route handlers.
+///
+/// It's implemented for:
+///
+/// * `Response<B>` itself.
+/// * `&'static str` and `String` as a `text/plain` body, and `Bytes` and `Vec<u8>` as an `application/octet-stream`
+///   body, provided the body type `B` can be created from them.
+/// * `StatusCode` and `()` as an empty body.
+/// * `(StatusCode, T)` to override the status of `T`.
+/// * `(HeaderMap, T)` and `([(K, V); N], T)` to add the headers to `T`, and the same with a leading `StatusCode`.
+/// * `Result<T, E>` where both `T` and `E` are convertible into a response.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{header, Body, StatusCode};
+/// use routerify::Router;
+/// use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router: Router<Body, Infallible> = Router::builder()
+///     .get("/", || async move { Ok("Home page") })
+///     .get(
+///         "/users/:userId",
+///         || async move { Ok((StatusCode::NOT_FOUND, [(header::CACHE_CONTROL, "no-store")], "User not found")) },
+///     )
+///     .delete("/users/:userId", || async move { Ok(StatusCode::NO_CONTENT) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub trait IntoResponse<B> {
+    /// Converts the value into a response.
+    fn into_response(self) -> Response<B>;
+}
+
+impl<B> IntoResponse<B> for Response<B> {
+    fn into_response(self) -> Response<B> {
+        self
+    }
+}
+
+impl<B: From<&'static str>> IntoResponse<B> for &'static str {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "text/plain; charset=utf-8")
+    }
+}
+
+impl<B: From<String>> IntoResponse<B> for String {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "text/plain; charset=utf-8")
+    }
+}
+
+impl<B: From<Bytes>> IntoResponse<B> for Bytes {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "application/octet-stream")
+    }
+}
+
+impl<B: From<Vec<u8>>> IntoResponse<B> for Vec<u8> {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "application/octet-stream")
+    }
+}
+
+impl<B: From<&'static str>> IntoResponse<B> for StatusCode {
+    fn into_response(self) -> Response<B> {
+        let mut res = Response::new(B::from(""));
+        *res.status_mut() = self;
+        res
+    }
+}
+
+impl<B: From<&'static str>> IntoResponse<B> for () {
+    fn into_response(self) -> Response<B> {
+        Response::new(B::from(""))
+    }
+}
+
+impl<B, T: IntoResponse<B>> IntoResponse<B> for (StatusCode, T) {
+    fn into_response(self) -> Response<B> {
+        let mut res = self.1.into_response();
+        *res.status_mut() = self.0;
+        res
+    }
+}
+
+impl<B, T: IntoResponse<B>> IntoResponse<B> for (HeaderMap, T) {
+    fn into_response(self) -> Response<B> {
+        let mut res = self.1.into_response();
+        res.headers_mut().extend(self.0);
+        res
+    }
+}
+
+impl<B, T: IntoResponse<B>> IntoResponse<B> for (StatusCode, HeaderMap, T) {
+    fn into_response(self) -> Response<B> {
+        (self.0, (self.1, self.2)).into_response()
+    }
+}
+
+impl<B, T, K, V, const N: usize> IntoResponse<B> for ([(K, V); N], T)
+where
+    B: From<&'static str>,
+    T: IntoResponse<B>,
+    HeaderName: TryFrom<K>,
+    <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+    HeaderValue: TryFrom<V>,
+    <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+{
+    fn into_response(self) -> Response<B> {
+        match header_map(self.0) {
+            Ok(headers) => (headers, self.1).into_response(),
+            Err(err) => {
+                log::error!("Couldn't convert the response headers: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+impl<B, T, K, V, const N: usize> IntoResponse<B> for (StatusCode, [(K, V); N], T)
+where
+    B: From<&'static str>,
+    T: IntoResponse<B>,
+    HeaderName: TryFrom<K>,
+    <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+    HeaderValue: TryFrom<V>,
+    <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+{
+    fn into_response(self) -> Response<B> {
+        match header_map(self.1) {
+            Ok(headers) => (self.0, headers, self.2).into_response(),
+            Err(err) => {
+                log::error!("Couldn't convert the response headers: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+impl<B, T: IntoResponse<B>, E: IntoResponse<B>> IntoResponse<B> for Result<T, E> {
+    fn into_response(self) -> Response<B> {
+        match self {
+            Ok(val) => val.into_response(),
+            Err(err) => err.into_response(),
+        }
+    }
+}
+
+/// A response body type which the default responses are built with, i.e. the `404 Not Found` route, the global
+/// `OPTIONS` route, the error handler and the shutdown response.
+///
+/// It's implemented for any body type which can be built from a `&'static str` and a `String`, so they get the default
+/// responses without any setup. A custom body type which can't be built from a string can implement it by returning
+/// `None`, the default responses aren't added then and a warning is reported via the [`log`](https://docs.rs/log)
+/// crate instead.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::body::{Bytes, HttpBody};
+/// use hyper::HeaderMap;
+/// use routerify::DefaultBody;
+/// use std::borrow::Cow;
+/// use std::convert::Infallible;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll};
+///
+/// struct EmptyBody;
+///
+/// impl HttpBody for EmptyBody {
+///     type Data = Bytes;
+///     type Error = Infallible;
+///
+///     fn poll_data(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Result<Bytes, Infallible>>> {
+///         Poll::Ready(None)
+///     }
+///
+///     fn poll_trailers(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Infallible>> {
+///         Poll::Ready(Ok(None))
+///     }
+/// }
+///
+/// impl DefaultBody for EmptyBody {
+///     fn from_text(_: Cow<'static, str>) -> Option<Self> {
+///         None
+///     }
+/// }
+/// ```
+pub trait DefaultBody: Sized {
+    /// Builds the body of a default response from its text, or returns `None` if the default responses can't be
+    /// built with this body type.
+    fn from_text(text: Cow<'static, str>) -> Option<Self>;
+}
+
+impl<B: From<&'static str> + From<String>> DefaultBody for B {
+    fn from_text(text: Cow<'static, str>) -> Option<Self> {
+        match text {
+            Cow::Borrowed(text) => Some(B::from(text)),
+            Cow::Owned(text) => Some(B::from(text)),
+        }
+    }
+}
+
+fn with_content_type<B>(body: B, content_type: &'static str) -> Response<B> {
+    let mut res = Response::new(body);
+    res.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    res
+}
+
+fn header_map<K, V, const N: usize>(headers: [(K, V); N]) -> Result<HeaderMap, http::Error>
+where
+    HeaderName: TryFrom<K>,
+    <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+    HeaderValue: TryFrom<V>,
+    <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+{
+    let mut map = HeaderMap::with_capacity(N);
+
+    for (key, val) in IntoIterator::into_iter(headers) {
+        map.append(
+            HeaderName::try_from(key).map_err(Into::into)?,
+            HeaderValue::try_from(val).map_err(Into::into)?,
+        );
+    }
+
+    Ok(map)
+}
@@ -1,27 +1,42 @@
+use crate::extract;
 use crate::helpers;
 use crate::regex_generator::generate_exact_match_regex;
-use crate::types::{RequestMeta, RouteParams};
+use crate::types::{QueryCache, RequestMeta, RouteParams};
 use crate::Error;
+use crate::RouteError;
+use hyper::service::Service;
 use hyper::{body::HttpBody, Method, Request, Response};
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
-use std::future::Future;
+use std::future::{poll_fn, Future};
+use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
-type Handler<B, E> = Box<dyn Fn(Request<hyper::Body>) -> HandlerReturn<B, E> + Send + Sync + 'static>;
-type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+// The handler error is boxed into `RouteError` right away, so that route handlers
+// can also be backed by services or layers with their own error types.
+pub(crate) type Handler<B, ReqBody> = Box<dyn Fn(Request<ReqBody>) -> HandlerReturn<B> + Send + Sync + 'static>;
+pub(crate) type HandlerReturn<B> = extract::HandlerFuture<B>;
+
+// Tells whether a route accepts a request matching its path and its methods, the next matching route gets it
+// otherwise. The returned future must not borrow the request, as the request body isn't required to be `Sync`.
+pub(crate) type Guard<ReqBody> = Box<dyn Fn(&Request<ReqBody>, &RouteParams) -> GuardReturn + Send + Sync + 'static>;
+pub(crate) type GuardReturn = Pin<Box<dyn Future<Output = bool> + Send + 'static>>;
 
 /// Represents a single route.
 ///
 /// A route consists of a path, http method type(s) and a handler. It shouldn't be created directly, use [RouterBuilder](./struct.RouterBuilder.html) methods
 /// to create a route.
 ///
-/// This `Route<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Route<B, E, ReqBody>` type accepts three type parameters: `B`, `E` and `ReqBody`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -43,25 +58,36 @@ type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Sen
 /// # }
 /// # run();
 /// ```
-pub struct Route<B, E> {
+pub struct Route<B, E, ReqBody = hyper::Body> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     route_params: Vec<String>,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<B, E>>,
+    pub(crate) handler: Option<Handler<B, ReqBody>>,
     pub(crate) methods: Vec<Method>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // Whether the path prefix i.e. the path without the trailing glob should be stripped from the
+    // request URI before calling the handler. It's used by the routes of the mounted services.
+    pub(crate) strip_prefix: bool,
+    // Same as the handler, it's an option so that it can be extracted out when the router is scoped.
+    pub(crate) guard: Option<Guard<ReqBody>>,
+    _error: PhantomData<fn() -> E>,
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<B, E> {
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Route<B, E, ReqBody>
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
         methods: Vec<Method>,
-        handler: Handler<B, E>,
+        handler: Handler<B, ReqBody>,
         scope_depth: u32,
-    ) -> crate::Result<Route<B, E>> {
+    ) -> crate::Result<Route<B, E, ReqBody>> {
         let path = path.into();
         let (re, params) = generate_exact_match_regex(path.as_str()).map_err(|e| {
             Error::new(format!(
@@ -77,16 +103,19 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             handler: Some(handler),
             methods,
             scope_depth,
+            strip_prefix: false,
+            guard: None,
+            _error: PhantomData,
         })
     }
 
-    pub(crate) fn new<P, H, R>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<B, E>>
+    pub(crate) fn new<P, H, T>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<B, E, ReqBody>>
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: extract::Handler<T, B, E, ReqBody>,
     {
-        let handler: Handler<B, E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        let handler = Arc::new(handler);
+        let handler: Handler<B, ReqBody> = Box::new(move |req: Request<ReqBody>| handler.clone().call(req));
         Route::new_with_boxed_handler(path, methods, handler, 1)
     }
 
@@ -94,26 +123,48 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         self.methods.contains(method)
     }
 
-    pub(crate) async fn process(&self, target_path: &str, mut req: Request<hyper::Body>) -> crate::Result<Response<B>> {
+    // It's not an async method, so that the request isn't borrowed while the guard is awaited.
+    pub(crate) fn check_guard(&self, target_path: &str, req: &Request<ReqBody>) -> Option<GuardReturn> {
+        self.guard
+            .as_ref()
+            .map(|guard| guard(req, &self.generate_route_params(target_path)))
+    }
+
+    pub(crate) async fn process(&self, target_path: &str, mut req: Request<ReqBody>) -> crate::Result<Response<B>> {
         self.push_req_meta(target_path, &mut req);
 
+        // The pre middlewares could have rewritten the query string after it was cached.
+        if !matches!(req.extensions().get::<QueryCache>(), Some(cache) if cache.is_valid_for(req.uri())) {
+            let query_cache = QueryCache::new(req.uri());
+            req.extensions_mut().insert(query_cache);
+        }
+
+        if self.strip_prefix {
+            let segments = self.path.split('/').filter(|s| !s.is_empty() && *s != "*").count();
+            *req.uri_mut() = helpers::strip_path_segments(req.uri(), segments)?;
+        }
+
         let handler = self
             .handler
             .as_ref()
             .expect("A router can not be used after mounting into another router");
 
-        Pin::from(handler(req)).await.map_err(Into::into)
+        handler(req).await
     }
 
-    fn push_req_meta(&self, target_path: &str, req: &mut Request<hyper::Body>) {
+    fn push_req_meta(&self, target_path: &str, req: &mut Request<ReqBody>) {
         self.update_req_meta(req, self.generate_req_meta(target_path));
     }
 
-    fn update_req_meta(&self, req: &mut Request<hyper::Body>, req_meta: RequestMeta) {
+    fn update_req_meta(&self, req: &mut Request<ReqBody>, req_meta: RequestMeta) {
         helpers::update_req_meta_in_extensions(req.extensions_mut(), req_meta);
     }
 
     fn generate_req_meta(&self, target_path: &str) -> RequestMeta {
+        RequestMeta::with_route_params(self.generate_route_params(target_path))
+    }
+
+    fn generate_route_params(&self, target_path: &str) -> RouteParams {
         let route_params_list = &self.route_params;
         let ln = route_params_list.len();
 
@@ -132,11 +183,38 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             }
         }
 
-        RequestMeta::with_route_params(route_params)
+        route_params
     }
 }
 
-impl<B, E> Debug for Route<B, E> {
+// Turns a service into a route handler. All the requests are dispatched to the same service, so the stateful ones like
+// the rate or the concurrency limits apply to the route. The fair lock is held while the service is driven to readiness
+// and called, so the requests wait for it in turn, but not while the response future is awaited.
+pub(crate) fn service_handler<S, B, ReqBody>(service: S) -> Handler<B, ReqBody>
+where
+    S: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+    S::Error: Into<RouteError>,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    let service = Arc::new(Mutex::new(service));
+
+    Box::new(move |req: Request<ReqBody>| {
+        let service = service.clone();
+
+        Box::pin(async move {
+            let fut = {
+                let mut service = service.lock().await;
+                poll_fn(|cx| service.poll_ready(cx)).await.map_err(Into::into)?;
+                service.call(req)
+            };
+
+            fut.await.map_err(Into::into)
+        })
+    })
+}
+
+impl<B, E, ReqBody> Debug for Route<B, E, ReqBody> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
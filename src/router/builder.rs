@@ -1,23 +1,36 @@
 use crate::constants;
 use crate::data_map::{DataMap, ScopedDataMap};
+use crate::extract::Handler;
 use crate::middleware::{Middleware, PostMiddleware, PreMiddleware};
-use crate::route::Route;
+use crate::proxy::TrustedProxies;
+use crate::response::DefaultBody;
+#[cfg(feature = "fs")]
+use crate::route::GuardReturn;
+use crate::route::{self, Route};
 use crate::router::Router;
-use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo};
-use crate::types::RequestInfo;
-use hyper::{body::HttpBody, Method, Request, Response};
+use crate::router::{self, ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo};
+#[cfg(feature = "tower")]
+use crate::tower::HandlerService;
+#[cfg(feature = "fs")]
+use crate::types::RouteParams;
+use crate::types::{ForwardedHeader, RequestInfo};
+use hyper::{body::HttpBody, service::Service, Method, Request, Response};
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
+#[cfg(feature = "tower")]
+use tower_layer::Layer;
 
 /// Builder for the [Router](./struct.Router.html) type.
 ///
-/// This `RouterBuilder<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterBuilder<B, E, ReqBody>` type accepts three type parameters: `B`, `E` and `ReqBody`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -50,28 +63,39 @@ use std::sync::Arc;
 /// # }
 /// # run();
 /// ```
-pub struct RouterBuilder<B, E> {
-    inner: crate::Result<BuilderInner<B, E>>,
+pub struct RouterBuilder<B, E, ReqBody = hyper::Body> {
+    inner: crate::Result<BuilderInner<B, E, ReqBody>>,
 }
 
-struct BuilderInner<B, E> {
-    pre_middlewares: Vec<PreMiddleware<E>>,
-    routes: Vec<Route<B, E>>,
+struct BuilderInner<B, E, ReqBody> {
+    pre_middlewares: Vec<PreMiddleware<E, ReqBody>>,
+    routes: Vec<Route<B, E, ReqBody>>,
     post_middlewares: Vec<PostMiddleware<B, E>>,
     data_maps: HashMap<String, Vec<DataMap>>,
     err_handler: Option<ErrHandler<B>>,
+    trusted_proxies: TrustedProxies,
+    reject_during_shutdown: bool,
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RouterBuilder<B, E, ReqBody>
 {
     /// Creates a new `RouterBuilder` instance with default options.
-    pub fn new() -> RouterBuilder<B, E> {
+    pub fn new() -> RouterBuilder<B, E, ReqBody> {
         RouterBuilder::default()
     }
 
     /// Creates a new [Router](./struct.Router.html) instance from the added configuration.
-    pub fn build(self) -> crate::Result<Router<B, E>> {
+    ///
+    /// The default responses are added if the response body type can be built from a string, see the
+    /// [`DefaultBody`](./trait.DefaultBody.html) trait.
+    pub fn build(self) -> crate::Result<Router<B, E, ReqBody>>
+    where
+        B: DefaultBody,
+    {
         self.inner.and_then(|inner| {
             let scoped_data_maps = inner
                 .data_maps
@@ -91,13 +115,16 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
                 inner.post_middlewares,
                 scoped_data_maps,
                 inner.err_handler,
+                inner.trusted_proxies,
+                inner.reject_during_shutdown,
+                router::default_body_fn::<B>(),
             ))
         })
     }
 
     fn and_then<F>(self, func: F) -> Self
     where
-        F: FnOnce(BuilderInner<B, E>) -> crate::Result<BuilderInner<B, E>>,
+        F: FnOnce(BuilderInner<B, E, ReqBody>) -> crate::Result<BuilderInner<B, E, ReqBody>>,
     {
         RouterBuilder {
             inner: self.inner.and_then(func),
@@ -105,8 +132,11 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RouterBuilder<B, E, ReqBody>
 {
     /// Adds a new route with `GET` method and the handler at the specified path.
     ///
@@ -129,11 +159,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn get<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn get<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::GET], handler)
     }
@@ -159,11 +188,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn get_or_head<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn get_or_head<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::GET, Method::HEAD], handler)
     }
@@ -189,11 +217,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn post<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn post<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::POST], handler)
     }
@@ -219,11 +246,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn put<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn put<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::PUT], handler)
     }
@@ -249,11 +275,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn delete<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn delete<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::DELETE], handler)
     }
@@ -279,11 +304,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn head<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn head<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::HEAD], handler)
     }
@@ -309,11 +333,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn trace<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn trace<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::TRACE], handler)
     }
@@ -339,11 +362,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn connect<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn connect<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::CONNECT], handler)
     }
@@ -369,11 +391,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn patch<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn patch<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::PATCH], handler)
     }
@@ -399,11 +420,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn options<P, H, R>(self, path: P, handler: H) -> Self
+    pub fn options<P, H, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, vec![Method::OPTIONS], handler)
     }
@@ -440,10 +460,9 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn any<H, R>(self, handler: H) -> Self
+    pub fn any<H, T>(self, handler: H) -> Self
     where
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
@@ -470,11 +489,10 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn any_method<H, R, P>(self, path: P, handler: H) -> Self
+    pub fn any_method<H, T, P>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.add(path, constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
@@ -500,20 +518,13 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn add<P, H, R>(self, path: P, methods: Vec<Method>, handler: H) -> Self
+    pub fn add<P, H, T>(self, path: P, methods: Vec<Method>, handler: H) -> Self
     where
         P: Into<String>,
-        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Handler<T, B, E, ReqBody>,
     {
         self.and_then(move |mut inner| {
-            let mut path = path.into();
-
-            if !path.ends_with('/') && !path.ends_with('*') {
-                path.push('/');
-            }
-
-            let route = Route::new(path, methods, handler)?;
+            let route = Route::new(route_path(path), methods, handler)?;
             inner.routes.push(route);
 
             crate::Result::Ok(inner)
@@ -534,8 +545,8 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     ///
     ///     pub fn router() -> Router<Body, hyper::Error> {
     ///         Router::builder()
-    ///          .get("/users", |req| async move { Ok(Response::new(Body::from("User list"))) })
-    ///          .get("/books", |req| async move { Ok(Response::new(Body::from("Book list"))) })
+    ///          .get("/users", |req: Request<Body>| async move { Ok(Response::new(Body::from("User list"))) })
+    ///          .get("/books", |req: Request<Body>| async move { Ok(Response::new(Body::from("Book list"))) })
     ///          .build()
     ///          .unwrap()
     ///     }
@@ -553,7 +564,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// ```
     ///
     /// Now, the app can handle requests on: `/api/users` and `/api/books` paths.
-    pub fn scope<P>(self, path: P, mut router: Router<B, E>) -> Self
+    pub fn scope<P>(self, path: P, mut router: Router<B, E, ReqBody>) -> Self
     where
         P: Into<String>,
     {
@@ -581,12 +592,19 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         }
 
         for route in router.routes.iter_mut() {
+            let strip_prefix = route.strip_prefix;
+            let guard = route.guard.take();
             let new_route = Route::new_with_boxed_handler(
                 format!("{}{}", path.as_str(), route.path.as_str()),
                 route.methods.clone(),
                 route.handler.take().expect("No handler found in one of the routes"),
                 route.scope_depth + 1,
-            );
+            )
+            .map(|mut new_route| {
+                new_route.strip_prefix = strip_prefix;
+                new_route.guard = guard;
+                new_route
+            });
             builder = builder.and_then(move |mut inner| {
                 inner.routes.push(new_route?);
                 crate::Result::Ok(inner)
@@ -634,10 +652,121 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 
         builder
     }
+
+    /// Mounts a [`Service`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html) e.g. a third-party admin UI or a
+    /// metrics exporter at the specified path prefix.
+    ///
+    /// All the requests at the prefix or under the prefix are forwarded to the service regardless of their method. As it's
+    /// mounted as a route, the pre middlewares, the post middlewares and the data matching the prefix still apply. All the
+    /// requests are dispatched to the same service and any error it returns is propagated into the error handler.
+    ///
+    /// The request is forwarded with its original URI, use [`mount_with_stripped_prefix`](#method.mount_with_stripped_prefix)
+    /// if the service expects the paths relative to the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RouteError, Router};
+    /// use hyper::{service::service_fn, Response, Request, Body};
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let metrics = service_fn(|req: Request<Body>| async move {
+    ///     Ok::<_, RouteError>(Response::new(Body::from(format!("Metrics at {}", req.uri().path()))))
+    /// });
+    ///
+    /// let router = Router::builder()
+    ///     // Handles `/metrics`, `/metrics/cpu` and so on.
+    ///     .mount("/metrics", metrics)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn mount<P, S>(self, prefix: P, service: S) -> Self
+    where
+        P: Into<String>,
+        S: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+        S::Error: Into<crate::RouteError>,
+        S::Future: Send + 'static,
+    {
+        self.mount_service(prefix.into(), service, false)
+    }
+
+    /// Mounts a [`Service`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html) at the specified path prefix
+    /// like the [`mount`](#method.mount) method, but the prefix is stripped from the request URI before it's forwarded,
+    /// so a request to `/admin/users?page=2` reaches a service mounted at `/admin` as `/users?page=2`.
+    ///
+    /// The prefix may contain route parameters, which are available to the service through the
+    /// [`RequestExt`](./ext/trait.RequestExt.html) methods. Glob patterns are not supported in the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RouteError, Router};
+    /// use routerify::prelude::*;
+    /// use hyper::{service::service_fn, Response, Request, Body};
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let admin_ui = service_fn(|req: Request<Body>| async move {
+    ///     let tenant = req.param("tenant").unwrap();
+    ///     Ok::<_, RouteError>(Response::new(Body::from(format!("Admin page {} of {}", req.uri().path(), tenant))))
+    /// });
+    ///
+    /// let router = Router::builder()
+    ///     .mount_with_stripped_prefix("/admin/:tenant", admin_ui)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn mount_with_stripped_prefix<P, S>(self, prefix: P, service: S) -> Self
+    where
+        P: Into<String>,
+        S: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+        S::Error: Into<crate::RouteError>,
+        S::Future: Send + 'static,
+    {
+        self.mount_service(prefix.into(), service, true)
+    }
+
+    fn mount_service<S>(self, prefix: String, service: S, strip_prefix: bool) -> Self
+    where
+        S: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+        S::Error: Into<crate::RouteError>,
+        S::Future: Send + 'static,
+    {
+        self.and_then(move |mut inner| {
+            let prefix = prefix.trim_end_matches('/');
+
+            if strip_prefix && prefix.contains('*') {
+                return Err(crate::Error::new(format!(
+                    "Glob patterns are not supported in a mount prefix with stripping: {}",
+                    prefix
+                ))
+                .into());
+            }
+
+            let mut route = Route::new_with_boxed_handler(
+                format!("{}/*", prefix),
+                constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(),
+                route::service_handler(service),
+                1,
+            )?;
+            route.strip_prefix = strip_prefix;
+            inner.routes.push(route);
+
+            crate::Result::Ok(inner)
+        })
+    }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RouterBuilder<B, E, ReqBody>
 {
     /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
     /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
@@ -661,7 +790,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     /// # }
     /// # run();
     /// ```
-    pub fn middleware(self, m: Middleware<B, E>) -> Self {
+    pub fn middleware(self, m: Middleware<B, E, ReqBody>) -> Self {
         self.and_then(move |mut inner| {
             match m {
                 Middleware::Pre(middleware) => {
@@ -730,12 +859,205 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             crate::Result::Ok(inner)
         })
     }
+
+    /// Sets the proxies whose forwarding headers are trusted, as a list of CIDR ranges e.g. `10.0.0.0/8` or single IP
+    /// addresses.
+    ///
+    /// If the peer of the connection is in one of the ranges, the client address, the scheme and the host are resolved
+    /// from the `Forwarded`, `X-Forwarded-For`, `X-Forwarded-Proto`, `X-Forwarded-Host` and `X-Real-IP` headers. The
+    /// result is available via the [`RequestExt::client_info`](./ext/trait.RequestExt.html#method.client_info) method,
+    /// while the [`RequestExt::remote_addr`](./ext/trait.RequestExt.html#method.remote_addr) method still returns the
+    /// peer address.
+    ///
+    /// By default, the first of these headers which is present is used, so the proxies must strip the ones they don't
+    /// set, otherwise the client can spoof its address through them. Use the
+    /// [`forwarded_header`](#method.forwarded_header) method to trust only the header which the proxies set.
+    ///
+    /// Like the error handler, it should be set only on the root router, it's ignored for the scoped routers.
+    ///
+    /// Please refer to the [ClientInfo](./struct.ClientInfo.html) for an example.
+    pub fn trusted_proxies<I, S>(self, ranges: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let trusted_proxies = TrustedProxies::parse(ranges);
+
+        self.and_then(move |mut inner| {
+            let header = inner.trusted_proxies.header;
+            inner.trusted_proxies = trusted_proxies?;
+            inner.trusted_proxies.header = header;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the only forwarding header which is trusted when the peer is one of the
+    /// [trusted proxies](#method.trusted_proxies), the others are ignored.
+    ///
+    /// If the header is missing, the client info is resolved from the peer address. If it's malformed, the client
+    /// address is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{ForwardedHeader, Router};
+    /// # use hyper::{Body, Response};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .trusted_proxies(vec!["10.0.0.0/8"])
+    ///     .forwarded_header(ForwardedHeader::XForwardedFor)
+    ///     .get("/", || async move { Ok(Response::new(Body::from("Hello"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn forwarded_header(self, header: ForwardedHeader) -> Self {
+        self.and_then(move |mut inner| {
+            inner.trusted_proxies.header = Some(header);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    // Only the requests accepted by the guard are handled by the routes added so far, the other ones are passed to the
+    // next matching route e.g. the catch-all route of the parent router.
+    #[cfg(feature = "fs")]
+    pub(crate) fn guard<G>(self, guard: G) -> Self
+    where
+        G: Fn(&Request<ReqBody>, &RouteParams) -> GuardReturn + Clone + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            for route in inner.routes.iter_mut() {
+                route.guard = Some(Box::new(guard.clone()));
+            }
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the new requests are answered with `503 Service Unavailable` and a `Connection: close` header
+    /// once the [shutdown](./struct.Shutdown.html) is triggered. The requests already in flight are not affected.
+    ///
+    /// The rejected requests skip the pre middlewares and the route handlers, but the response goes through the post
+    /// middlewares matching the request path e.g. to add the CORS headers.
+    ///
+    /// It's disabled by default, so the handlers keep processing the new requests and they can observe the shutdown
+    /// via the [`RequestExt::shutdown`](./ext/trait.RequestExt.html#method.shutdown) method.
+    ///
+    /// Like the error handler, it should be set only on the root router, it's ignored for the scoped routers.
+    pub fn reject_during_shutdown(self, reject: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.reject_during_shutdown = reject;
+            crate::Result::Ok(inner)
+        })
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RouterBuilder<B, E, ReqBody>
+{
+    /// Wraps all the routes added so far with a [`tower::Layer`](https://docs.rs/tower/0.4.13/tower/trait.Layer.html).
+    ///
+    /// The layer is applied to every route handler separately and it runs after the pre middlewares and before the
+    /// post middlewares. Routes added after this call are not wrapped, so a layer can be limited to a scope by applying
+    /// it to the sub-router before mounting it with the [`scope`](#method.scope) method.
+    ///
+    /// Any error returned by the layered service is propagated into the error handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RouteError, Router};
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    /// use tower::timeout::TimeoutLayer;
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let api: Router<Body, RouteError> = Router::builder()
+    ///     .get("/users", || async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .layer(TimeoutLayer::new(Duration::from_secs(5)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .scope("/api", api)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn layer<L>(self, layer: L) -> Self
+    where
+        L: Layer<HandlerService<B, ReqBody>>,
+        L::Service: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+        <L::Service as Service<Request<ReqBody>>>::Error: Into<crate::RouteError>,
+        <L::Service as Service<Request<ReqBody>>>::Future: Send + 'static,
+    {
+        self.and_then(move |mut inner| {
+            for route in inner.routes.iter_mut() {
+                let handler = route.handler.take().expect("No handler found in one of the routes");
+                route.handler = Some(route::service_handler(layer.layer(HandlerService::new(handler))));
+            }
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a new route with the specified method(s) at the specified path which is handled by a
+    /// [`tower::Service`](https://docs.rs/tower/0.4.13/tower/trait.Service.html).
+    ///
+    /// Use a glob path e.g. `/metrics/*` to hand over all the requests under a path prefix to the service. All the
+    /// requests are dispatched to the same service and any error it returns is propagated into the error handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RouteError, Router};
+    /// use hyper::{Response, Body, Method};
+    /// use tower::service_fn;
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let metrics = service_fn(|_req| async move { Ok::<_, RouteError>(Response::new(Body::from("metrics"))) });
+    ///
+    /// let router = Router::builder()
+    ///     .route_service("/metrics/*", vec![Method::GET], metrics)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn route_service<P, S>(self, path: P, methods: Vec<Method>, service: S) -> Self
+    where
+        P: Into<String>,
+        S: Service<Request<ReqBody>, Response = Response<B>> + Send + 'static,
+        S::Error: Into<crate::RouteError>,
+        S::Future: Send + 'static,
+    {
+        self.and_then(move |mut inner| {
+            let route = Route::new_with_boxed_handler(route_path(path), methods, route::service_handler(service), 1)?;
+            inner.routes.push(route);
+
+            crate::Result::Ok(inner)
+        })
+    }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Default
-    for RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Default for RouterBuilder<B, E, ReqBody>
 {
-    fn default() -> RouterBuilder<B, E> {
+    fn default() -> RouterBuilder<B, E, ReqBody> {
         RouterBuilder {
             inner: Ok(BuilderInner {
                 pre_middlewares: Vec::new(),
@@ -743,7 +1065,20 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
                 post_middlewares: Vec::new(),
                 data_maps: HashMap::new(),
                 err_handler: None,
+                trusted_proxies: TrustedProxies::default(),
+                reject_during_shutdown: false,
             }),
         }
     }
 }
+
+// The route paths end with a slash unless they end with a glob.
+fn route_path<P: Into<String>>(path: P) -> String {
+    let mut path = path.into();
+
+    if !path.ends_with('/') && !path.ends_with('*') {
+        path.push('/');
+    }
+
+    path
+}
@@ -1,13 +1,17 @@
 use crate::constants;
 use crate::data_map::ScopedDataMap;
+use crate::extract::Rejection;
 use crate::middleware::{PostMiddleware, PreMiddleware};
+use crate::proxy::TrustedProxies;
+use crate::response::DefaultBody;
 use crate::route::Route;
-use crate::types::RequestInfo;
+use crate::types::{ParamError, QueryError, RequestInfo, Shutdown};
 use crate::Error;
 use crate::RouteError;
-use hyper::{body::HttpBody, header, Method, Request, Response, StatusCode};
+use hyper::body::HttpBody;
+use hyper::{header, Method, Request, Response, StatusCode};
 use regex::RegexSet;
-use std::any::Any;
+use std::borrow::Cow;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
@@ -16,6 +20,9 @@ pub use self::builder::RouterBuilder;
 
 mod builder;
 
+// Builds the body of the default responses from a text.
+pub(crate) type DefaultBodyFn<B> = fn(Cow<'static, str>) -> B;
+
 pub(crate) type ErrHandlerWithoutInfo<B> =
     Box<dyn Fn(RouteError) -> ErrHandlerWithoutInfoReturn<B> + Send + Sync + 'static>;
 pub(crate) type ErrHandlerWithoutInfoReturn<B> = Box<dyn Future<Output = Response<B>> + Send + 'static>;
@@ -28,12 +35,14 @@ pub(crate) type ErrHandlerWithInfoReturn<B> = Box<dyn Future<Output = Response<B
 ///
 /// A router consists of some routes, some pre-middlewares and some post-middlewares.
 ///
-/// This `Router<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Router<B, E, ReqBody>` type accepts three type parameters: `B`, `E` and `ReqBody`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
 ///
 /// A `Router` can be created using the `Router::builder()` method.
 ///
@@ -59,9 +68,9 @@ pub(crate) type ErrHandlerWithInfoReturn<B> = Box<dyn Future<Output = Response<B
 /// # }
 /// # run();
 /// ```
-pub struct Router<B, E> {
-    pub(crate) pre_middlewares: Vec<PreMiddleware<E>>,
-    pub(crate) routes: Vec<Route<B, E>>,
+pub struct Router<B, E, ReqBody = hyper::Body> {
+    pub(crate) pre_middlewares: Vec<PreMiddleware<E, ReqBody>>,
+    pub(crate) routes: Vec<Route<B, E, ReqBody>>,
     pub(crate) post_middlewares: Vec<PostMiddleware<B, E>>,
     pub(crate) scoped_data_maps: Vec<ScopedDataMap>,
 
@@ -69,6 +78,20 @@ pub struct Router<B, E> {
     // Any error handler attached to scoped router will be ignored.
     pub(crate) err_handler: Option<ErrHandler<B>>,
 
+    // Same as the error handler, only the trusted proxies of the root Router are used.
+    pub(crate) trusted_proxies: TrustedProxies,
+
+    // The shutdown state is shared by all the services created from this router.
+    pub(crate) shutdown: Shutdown,
+    reject_during_shutdown: bool,
+
+    // Builds the body of the default responses, it's set by the RouterBuilder if the body type implements
+    // DefaultBody trait.
+    pub(crate) default_body: Option<DefaultBodyFn<B>>,
+
+    // We'll initialize it from the RouterService via Router::init_shutdown_response() method.
+    pub(crate) shutdown_body: Option<DefaultBodyFn<B>>,
+
     // We'll initialize it from the RouterService via Router::init_regex_set() method.
     regex_set: Option<RegexSet>,
 
@@ -92,13 +115,22 @@ impl<B: HttpBody + Send + Sync + 'static> ErrHandler<B> {
     }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Router<B, E> {
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Router<B, E, ReqBody>
+{
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        pre_middlewares: Vec<PreMiddleware<E>>,
-        routes: Vec<Route<B, E>>,
+        pre_middlewares: Vec<PreMiddleware<E, ReqBody>>,
+        routes: Vec<Route<B, E, ReqBody>>,
         post_middlewares: Vec<PostMiddleware<B, E>>,
         scoped_data_maps: Vec<ScopedDataMap>,
         err_handler: Option<ErrHandler<B>>,
+        trusted_proxies: TrustedProxies,
+        reject_during_shutdown: bool,
+        default_body: Option<DefaultBodyFn<B>>,
     ) -> Self {
         Router {
             pre_middlewares,
@@ -106,6 +138,11 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             post_middlewares,
             scoped_data_maps,
             err_handler,
+            trusted_proxies,
+            shutdown: Shutdown::new(),
+            reject_during_shutdown,
+            default_body,
+            shutdown_body: None,
             regex_set: None,
             should_gen_req_info: None,
         }
@@ -153,109 +190,17 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     //     self.post_middlewares.push(keep_alive_post_middleware);
     // }
 
-    pub(crate) fn init_global_options_route(&mut self) {
-        let options_method = vec![Method::OPTIONS];
-        let found = self
-            .routes
-            .iter()
-            .any(|route| route.path == "/*" && route.methods.as_slice() == options_method.as_slice());
-
-        if found {
-            return;
-        }
-
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let options_route: Route<hyper::Body, E> = Route::new("/*", options_method, |_req| async move {
-                Ok(Response::builder()
-                    .status(StatusCode::NO_CONTENT)
-                    .body(hyper::Body::empty())
-                    .expect("Couldn't create the default OPTIONS response"))
-            })
-            .unwrap();
-
-            router.routes.push(options_route);
-        } else {
-            eprintln!(
-                "Warning: No global `options method` route added. It is recommended to send response to any `options` request.\n\
-                Please add one by calling `.options(\"/*\", handler)` method of the root router builder.\n"
-            );
-        }
-    }
-
-    pub(crate) fn init_default_404_route(&mut self) {
-        let found = self
-            .routes
-            .iter()
-            .any(|route| route.path == "/*" && route.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]);
-
-        if found {
-            return;
-        }
-
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let default_404_route: Route<hyper::Body, E> =
-                Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), |_req| async move {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(hyper::Body::from(StatusCode::NOT_FOUND.canonical_reason().unwrap()))
-                        .expect("Couldn't create the default 404 response"))
-                })
-                .unwrap();
-            router.routes.push(default_404_route);
-        } else {
-            eprintln!(
-                "Warning: No default 404 route added. It is recommended to send 404 response to any non-existent route.\n\
-                Please add one by calling `.any(handler)` method of the root router builder.\n"
-            );
-        }
-    }
-
-    pub(crate) fn init_err_handler(&mut self) {
-        let found = self.err_handler.is_some();
-
-        if found {
-            return;
-        }
-
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let handler: ErrHandler<hyper::Body> = ErrHandler::WithoutInfo(Box::new(move |err: RouteError| {
-                Box::new(async move {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(hyper::Body::from(format!(
-                            "{}: {}",
-                            StatusCode::INTERNAL_SERVER_ERROR.canonical_reason().unwrap(),
-                            err
-                        )))
-                        .expect("Couldn't create a response while handling the server error")
-                })
-            }));
-            router.err_handler = Some(handler);
-        } else {
-            eprintln!(
-                "Warning: No error handler added. It is recommended to add one to see what went wrong if any route or middleware fails.\n\
-                Please add one by calling `.err_handler(handler)` method of the root router builder.\n"
-            );
-        }
-    }
-
-    fn downcast_to_hyper_body_type(&mut self) -> Option<&mut Router<hyper::Body, E>> {
-        let any_obj: &mut dyn Any = self;
-        any_obj.downcast_mut::<Router<hyper::Body, E>>()
-    }
-
     /// Return a [RouterBuilder](./struct.RouterBuilder.html) instance to build a `Router`.
-    pub fn builder() -> RouterBuilder<B, E> {
+    pub fn builder() -> RouterBuilder<B, E, ReqBody> {
         builder::RouterBuilder::new()
     }
 
     pub(crate) async fn process(
         &self,
         target_path: &str,
-        mut req: Request<hyper::Body>,
+        mut req: Request<ReqBody>,
         mut req_info: Option<RequestInfo>,
+        rejected: Option<Response<B>>,
     ) -> crate::Result<Response<B>> {
         let (
             matched_pre_middleware_idxs,
@@ -290,9 +235,15 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         let ext = req.extensions_mut();
         ext.insert(shared_data_maps);
 
-        let res_pre = self
-            .execute_pre_middleware(req, matched_pre_middleware_idxs, route_scope_depth, req_info.clone())
-            .await?;
+        // The rejected requests e.g. during the shutdown skip the pre middlewares and the route handlers, but their
+        // response still goes through the post middlewares.
+        let res_pre = match rejected {
+            Some(rejected) => Err(rejected),
+            None => {
+                self.execute_pre_middleware(req, matched_pre_middleware_idxs, route_scope_depth, req_info.clone())
+                    .await?
+            }
+        };
 
         // If pre middlewares succeed then execute the route handler.
         // If a pre middleware fails and is able to generate error response
@@ -305,6 +256,13 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
                     let route = &self.routes[idx];
 
                     if route.is_match_method(transformed_req.method()) {
+                        let guard = route.check_guard(target_path, &transformed_req);
+                        if let Some(guard) = guard {
+                            if !guard.await {
+                                continue;
+                            }
+                        }
+
                         let route_resp_res = route.process(target_path, transformed_req).await;
 
                         let route_resp = match route_resp_res {
@@ -358,11 +316,11 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 
     async fn execute_pre_middleware(
         &self,
-        req: Request<hyper::Body>,
+        req: Request<ReqBody>,
         matched_pre_middleware_idxs: Vec<usize>,
         route_scope_depth: Option<u32>,
         req_info: Option<RequestInfo>,
-    ) -> crate::Result<Result<Request<hyper::Body>, Response<B>>> {
+    ) -> crate::Result<Result<Request<ReqBody>, Response<B>>> {
         let mut transformed_req = req;
         for idx in matched_pre_middleware_idxs {
             let pre_middleware = &self.pre_middlewares[idx];
@@ -428,7 +386,176 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     }
 }
 
-impl<B, E> Debug for Router<B, E> {
+// The defaults are created from strings, so they are available for the body types which can be built from them i.e.
+// the ones implementing the `DefaultBody` trait.
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Router<B, E, ReqBody>
+{
+    pub(crate) fn init_global_options_route(&mut self) {
+        let options_method = vec![Method::OPTIONS];
+        let found = self
+            .routes
+            .iter()
+            .any(|route| route.path == "/*" && route.methods.as_slice() == options_method.as_slice());
+
+        if found {
+            return;
+        }
+
+        let body = match self.default_body {
+            Some(body) => body,
+            None => {
+                log::warn!(
+                    "No global OPTIONS route added, it is recommended to send a response to any OPTIONS request. \
+                    Please add one by calling the `.options(\"/*\", handler)` method of the root router builder."
+                );
+                return;
+            }
+        };
+
+        log::debug!("No global OPTIONS route found, adding the default one");
+
+        let options_route = Route::new("/*", options_method, move || async move {
+            Ok(Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(body(Cow::Borrowed("")))
+                .expect("Couldn't create the default OPTIONS response"))
+        })
+        .unwrap();
+
+        self.routes.push(options_route);
+    }
+
+    pub(crate) fn init_default_404_route(&mut self) {
+        let found = self
+            .routes
+            .iter()
+            .any(|route| route.path == "/*" && route.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]);
+
+        if found {
+            return;
+        }
+
+        let body = match self.default_body {
+            Some(body) => body,
+            None => {
+                log::warn!(
+                    "No default 404 route added, it is recommended to send a 404 response to any non-existent route. \
+                    Please add one by calling the `.any(handler)` method of the root router builder."
+                );
+                return;
+            }
+        };
+
+        log::debug!("No catch-all route found, adding the default 404 route");
+
+        let default_404_route = Route::new(
+            "/*",
+            constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(),
+            move || async move {
+                Ok(Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .header(header::CONTENT_TYPE, "text/plain")
+                    .body(body(Cow::Borrowed(StatusCode::NOT_FOUND.canonical_reason().unwrap())))
+                    .expect("Couldn't create the default 404 response"))
+            },
+        )
+        .unwrap();
+
+        self.routes.push(default_404_route);
+    }
+
+    pub(crate) fn init_err_handler(&mut self) {
+        let found = self.err_handler.is_some();
+
+        if found {
+            return;
+        }
+
+        let body = match self.default_body {
+            Some(body) => body,
+            None => {
+                log::warn!(
+                    "No error handler added, it is recommended to add one to see what went wrong if any route or \
+                    middleware fails. Please add one by calling the `.err_handler(handler)` method of the root router \
+                    builder."
+                );
+                return;
+            }
+        };
+
+        log::debug!("No error handler found, adding the default one");
+
+        let handler: ErrHandler<B> = ErrHandler::WithoutInfo(Box::new(move |err: RouteError| {
+            // The rejected requests, the invalid route parameters and query strings get their own status and message.
+            let (status, msg) = if let Some(rejection) = err.downcast_ref::<Rejection>() {
+                (rejection.status(), rejection.message().to_owned())
+            } else if let Some(err) = err.downcast_ref::<ParamError>() {
+                (err.status(), err.to_string())
+            } else if let Some(err) = err.downcast_ref::<QueryError>() {
+                (err.status(), err.to_string())
+            } else {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "{}: {}",
+                        StatusCode::INTERNAL_SERVER_ERROR.canonical_reason().unwrap(),
+                        err
+                    ),
+                )
+            };
+
+            Box::new(async move {
+                Response::builder()
+                    .status(status)
+                    .header(header::CONTENT_TYPE, "text/plain")
+                    .body(body(Cow::Owned(msg)))
+                    .expect("Couldn't create a response while handling the server error")
+            })
+        }));
+
+        self.err_handler = Some(handler);
+    }
+
+    pub(crate) fn init_shutdown_response(&mut self) {
+        if !self.reject_during_shutdown {
+            return;
+        }
+
+        match self.default_body {
+            Some(body) => self.shutdown_body = Some(body),
+            None => log::warn!(
+                "The new requests can't be rejected during the shutdown, as the response body type can't be built \
+                from a string. Please implement the `DefaultBody` trait for it."
+            ),
+        }
+    }
+}
+
+pub(crate) fn shutdown_response<B>(body: DefaultBodyFn<B>) -> Response<B> {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(header::CONNECTION, "close")
+        .body(body(Cow::Borrowed(
+            StatusCode::SERVICE_UNAVAILABLE.canonical_reason().unwrap(),
+        )))
+        .expect("Couldn't create the shutdown response")
+}
+
+// The body type can't be built from a text if it can't build an empty one.
+pub(crate) fn default_body_fn<B: DefaultBody>() -> Option<DefaultBodyFn<B>> {
+    B::from_text(Cow::Borrowed("")).map(|_| default_body::<B> as DefaultBodyFn<B>)
+}
+
+fn default_body<B: DefaultBody>(text: Cow<'static, str>) -> B {
+    B::from_text(text).expect("Couldn't create the body of a default response")
+}
+
+impl<B, E, ReqBody> Debug for Router<B, E, ReqBody> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
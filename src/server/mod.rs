@@ -0,0 +1,324 @@
+use crate::service::{Connection, RouterService};
+use crate::types::Shutdown;
+use crate::Router;
+use hyper::body::HttpBody;
+use hyper::server::accept;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
+
+type ShutdownSignal = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Serves the router on the listener until a `SIGINT` or a `SIGTERM` signal is received.
+///
+/// It's a shorthand for `Server::new(router)?.serve(listener)`, please refer to the [`Server`](./struct.Server.html)
+/// for more info.
+///
+/// # Examples
+///
+/// ```no_run
+/// use hyper::{Body, Response};
+/// use routerify::Router;
+/// use std::convert::Infallible;
+/// use tokio::net::TcpListener;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let router: Router<Body, Infallible> = Router::builder()
+///         .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+///         .build()
+///         .unwrap();
+///
+///     let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+///
+///     if let Err(err) = routerify::serve(listener, router).await {
+///         eprintln!("Server error: {}", err);
+///     }
+/// }
+/// ```
+pub async fn serve<L, B, E>(listener: L, router: Router<B, E>) -> crate::Result<()>
+where
+    L: Listener,
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    <B as HttpBody>::Data: Send + Sync + 'static,
+    <B as HttpBody>::Error: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    Server::new(router)?.serve(listener).await
+}
+
+/// A server which wires a [`RouterService`](./struct.RouterService.html) to a [`Listener`](./trait.Listener.html)
+/// e.g. a `TcpListener` or a `UnixListener`.
+///
+/// The connections are served with HTTP/1, HTTP/2 or both, depending on the `hyper-http1` and the `hyper-http2`
+/// features. With both of them, the protocol is detected from the first bytes of the connection.
+///
+/// On a shutdown signal, which defaults to `SIGINT` (Ctrl-C) or `SIGTERM`, the router's [shutdown](./struct.Shutdown.html)
+/// is triggered, the server stops accepting new connections and waits for the open ones to finish, but no longer
+/// than the [drain timeout](#method.drain_timeout).
+///
+/// # Examples
+///
+/// ```no_run
+/// use hyper::{Body, Response};
+/// use routerify::{Router, Server};
+/// use std::{convert::Infallible, time::Duration};
+/// use tokio::net::TcpListener;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let router: Router<Body, Infallible> = Router::builder()
+///         .reject_during_shutdown(true)
+///         .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+///         .build()
+///         .unwrap();
+///
+///     let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+///
+///     Server::new(router)
+///         .unwrap()
+///         .drain_timeout(Duration::from_secs(10))
+///         .serve(listener)
+///         .await
+///         .unwrap();
+/// }
+/// ```
+pub struct Server<B, E> {
+    service: RouterService<B, E>,
+    signal: Option<ShutdownSignal>,
+    drain_timeout: Duration,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Server<B, E> {
+    /// Creates a new server for the router.
+    pub fn new(router: Router<B, E>) -> crate::Result<Server<B, E>> {
+        Ok(Server {
+            service: RouterService::new(router)?,
+            signal: None,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        })
+    }
+}
+
+impl<B, E> Server<B, E> {
+    /// Sets the future which starts the shutdown when it completes, instead of the `SIGINT` and the `SIGTERM` signals.
+    pub fn shutdown_signal<F: Future<Output = ()> + Send + 'static>(mut self, signal: F) -> Server<B, E> {
+        self.signal = Some(Box::pin(signal));
+        self
+    }
+
+    /// Sets how long the open connections are waited for after the shutdown is triggered, it defaults to 30 seconds.
+    pub fn drain_timeout(mut self, drain_timeout: Duration) -> Server<B, E> {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// Returns the [shutdown](./struct.Shutdown.html) handle of the router, triggering it also shuts the server down.
+    pub fn shutdown(&self) -> Shutdown {
+        self.service.shutdown()
+    }
+}
+
+impl<B, E> Server<B, E>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    <B as HttpBody>::Data: Send + Sync + 'static,
+    <B as HttpBody>::Error: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    /// Serves the router on the listener until the server is shut down.
+    pub async fn serve<L: Listener>(self, listener: L) -> crate::Result<()> {
+        let shutdown = self.service.shutdown();
+        let signal = self.signal.unwrap_or_else(|| Box::pin(default_signal()));
+
+        let mut listener = Accept::new(listener);
+        let incoming = accept::poll_fn(move |cx| listener.poll_accept(cx).map(|io| Some(Ok::<_, io::Error>(io))));
+
+        let graceful_shutdown = {
+            let shutdown = shutdown.clone();
+            async move {
+                tokio::select! {
+                    _ = signal => shutdown.trigger(),
+                    _ = shutdown.triggered() => {}
+                }
+            }
+        };
+
+        // The connections are closed when the drain timeout is reached.
+        let close = Shutdown::new();
+
+        let server = hyper::Server::builder(incoming)
+            .executor(Executor { close: close.clone() })
+            .serve(self.service)
+            .with_graceful_shutdown(graceful_shutdown);
+
+        let drain_timeout = self.drain_timeout;
+        let deadline = async {
+            shutdown.triggered().await;
+            tokio::time::sleep(drain_timeout).await;
+        };
+
+        tokio::select! {
+            res = server => res.map_err(Into::into),
+            _ = deadline => {
+                log::warn!(
+                    "Drain timeout reached with {} requests in flight, closing the remaining connections",
+                    shutdown.in_flight()
+                );
+                close.trigger();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<B, E> Debug for Server<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("shutdown", &self.service.shutdown())
+            .field("drain_timeout", &self.drain_timeout)
+            .finish()
+    }
+}
+
+// Spawns the connection tasks of the server on the tokio runtime, so they can be aborted after the drain timeout.
+#[derive(Clone)]
+struct Executor {
+    close: Shutdown,
+}
+
+impl<F> hyper::rt::Executor<F> for Executor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        let close = self.close.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = fut => {}
+                _ = close.triggered() => {}
+            }
+        });
+    }
+}
+
+/// A listener which accepts the connections served by a [`Server`](./struct.Server.html).
+///
+/// It's implemented for the tokio `TcpListener` and, on unix platforms, the `UnixListener`.
+pub trait Listener: Send + 'static {
+    /// The type of the accepted connections.
+    type Io: AsyncRead + AsyncWrite + Connection + Send + Unpin + 'static;
+
+    /// Polls to accept a new connection.
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Io>>;
+}
+
+impl Listener for TcpListener {
+    type Io = TcpStream;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Io>> {
+        TcpListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+#[cfg(unix)]
+impl Listener for tokio::net::UnixListener {
+    type Io = tokio::net::UnixStream;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Io>> {
+        tokio::net::UnixListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+// Accepts the connections without ever failing, like the `AddrIncoming` of hyper.
+//
+// The errors of a single connection e.g. a client resetting it before it's accepted, are skipped. The other errors e.g.
+// too many open files, are retried after a delay as they are usually resolved once the other connections are closed.
+pub(crate) struct Accept<L> {
+    listener: L,
+    timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<L: Listener> Accept<L> {
+    pub(crate) fn new(listener: L) -> Self {
+        Accept {
+            listener,
+            timeout: None,
+        }
+    }
+
+    pub(crate) fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<L::Io> {
+        if let Some(timeout) = self.timeout.as_mut() {
+            if timeout.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.timeout = None;
+        }
+
+        loop {
+            match self.listener.poll_accept(cx) {
+                Poll::Ready(Ok(io)) => return Poll::Ready(io),
+                Poll::Ready(Err(err)) if is_connection_error(&err) => {
+                    log::debug!("Couldn't accept a connection: {}", err);
+                }
+                Poll::Ready(Err(err)) => {
+                    log::error!(
+                        "Couldn't accept a connection, retrying in {:?}: {}",
+                        ACCEPT_ERROR_DELAY,
+                        err
+                    );
+
+                    let mut timeout = Box::pin(tokio::time::sleep(ACCEPT_ERROR_DELAY));
+                    if timeout.as_mut().poll(cx).is_pending() {
+                        self.timeout = Some(timeout);
+                        return Poll::Pending;
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+fn is_connection_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset
+    )
+}
+
+async fn default_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(err) => {
+                log::error!("Couldn't listen for the SIGTERM signal: {}", err);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+
+    log::info!("Shutdown signal received");
+}
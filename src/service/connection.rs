@@ -0,0 +1,121 @@
+use crate::types::{ConnectionInfo, PeerAddr};
+use hyper::server::conn::AddrStream;
+use tokio::io::DuplexStream;
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+/// Represents a connection which a [`RouterService`](./struct.RouterService.html) can be served over.
+///
+/// The `RouterService` implements hyper's `MakeService` for any connection type implementing this trait, so it works
+/// with hyper's default TCP incoming as well as with a custom [`Accept`](https://docs.rs/hyper/0.14.4/hyper/server/accept/trait.Accept.html)
+/// implementation e.g. over Unix domain sockets or TLS streams.
+///
+/// It's implemented for hyper's `AddrStream`, tokio's `TcpStream`, `UnixStream` and `DuplexStream`. With the `rustls`
+/// feature, it's also implemented for the `tokio-rustls` server `TlsStream` wrapping any other connection type, which
+/// fills in the negotiated ALPN protocol, the SNI hostname and the client certificate chain of the
+/// [`ConnectionInfo`](./struct.ConnectionInfo.html).
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Connection, PeerAddr};
+/// use std::path::PathBuf;
+///
+/// // A custom stream type which knows the socket path of the peer.
+/// struct MyStream {
+///     stream: tokio::io::DuplexStream,
+///     path: PathBuf,
+/// }
+///
+/// impl Connection for MyStream {
+///     fn peer(&self) -> PeerAddr {
+///         PeerAddr::Unix(Some(self.path.clone()))
+///     }
+/// }
+/// ```
+pub trait Connection {
+    /// Returns the address of the peer.
+    fn peer(&self) -> PeerAddr;
+
+    /// Returns the information of the connection which is passed to each request. By default, only the peer address
+    /// is filled in.
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo::new(self.peer())
+    }
+}
+
+impl Connection for AddrStream {
+    fn peer(&self) -> PeerAddr {
+        PeerAddr::Tcp(self.remote_addr())
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo::new(self.peer()).with_local_addr(PeerAddr::Tcp(self.local_addr()))
+    }
+}
+
+impl Connection for TcpStream {
+    fn peer(&self) -> PeerAddr {
+        self.peer_addr().map(PeerAddr::Tcp).unwrap_or(PeerAddr::Unknown)
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        let local_addr = self.local_addr().map(PeerAddr::Tcp).unwrap_or(PeerAddr::Unknown);
+        ConnectionInfo::new(self.peer()).with_local_addr(local_addr)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn peer(&self) -> PeerAddr {
+        self.peer_addr()
+            .map(|addr| unix_addr(&addr))
+            .unwrap_or(PeerAddr::Unknown)
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        let local_addr = self
+            .local_addr()
+            .map(|addr| unix_addr(&addr))
+            .unwrap_or(PeerAddr::Unknown);
+        ConnectionInfo::new(self.peer()).with_local_addr(local_addr)
+    }
+}
+
+#[cfg(unix)]
+fn unix_addr(addr: &tokio::net::unix::SocketAddr) -> PeerAddr {
+    PeerAddr::Unix(addr.as_pathname().map(|path| path.to_path_buf()))
+}
+
+impl Connection for DuplexStream {
+    fn peer(&self) -> PeerAddr {
+        PeerAddr::Unknown
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl<IO: Connection> Connection for tokio_rustls::server::TlsStream<IO> {
+    fn peer(&self) -> PeerAddr {
+        self.get_ref().0.peer()
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        let (io, conn) = self.get_ref();
+        let mut info = io.connection_info();
+
+        if let Some(alpn_protocol) = conn.alpn_protocol() {
+            info = info.with_alpn_protocol(alpn_protocol.to_vec());
+        }
+
+        if let Some(server_name) = conn.server_name() {
+            info = info.with_server_name(server_name);
+        }
+
+        if let Some(certs) = conn.peer_certificates() {
+            info = info.with_peer_certificates(certs.iter().map(|cert| cert.as_ref().to_vec()).collect());
+        }
+
+        info
+    }
+}
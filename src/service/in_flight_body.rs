@@ -0,0 +1,65 @@
+use crate::types::InFlight;
+use hyper::body::{HttpBody, SizeHint};
+use hyper::HeaderMap;
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The response body of a [`RequestService`](./struct.RequestService.html).
+///
+/// It wraps the body of the response returned by the router, and the request is counted as in flight by the router's
+/// [shutdown](./struct.Shutdown.html) handle until the body is finished or dropped. So, a streaming response is drained
+/// like any other request.
+pub struct InFlightBody<B> {
+    inner: Pin<Box<B>>,
+    in_flight: Option<InFlight>,
+}
+
+impl<B> InFlightBody<B> {
+    pub(crate) fn new(inner: B, in_flight: Option<InFlight>) -> InFlightBody<B> {
+        InFlightBody {
+            inner: Box::pin(inner),
+            in_flight,
+        }
+    }
+
+    /// Returns a reference to the wrapped body.
+    pub fn get_ref(&self) -> &B {
+        self.inner.as_ref().get_ref()
+    }
+}
+
+impl<B: HttpBody> HttpBody for InFlightBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let poll = self.inner.as_mut().poll_data(cx);
+        if let Poll::Ready(None) = poll {
+            self.in_flight = None;
+        }
+        poll
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let poll = self.inner.as_mut().poll_trailers(cx);
+        if poll.is_ready() {
+            self.in_flight = None;
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> Debug for InFlightBody<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "InFlightBody {{ in_flight: {} }}", self.in_flight.is_some())
+    }
+}
@@ -1,5 +1,9 @@
+pub use connection::Connection;
+pub use in_flight_body::InFlightBody;
 pub use request_service::{RequestService, RequestServiceBuilder};
 pub use router_service::RouterService;
 
+mod connection;
+mod in_flight_body;
 mod request_service;
 mod router_service;
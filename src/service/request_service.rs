@@ -1,23 +1,26 @@
 use crate::helpers;
-use crate::router::Router;
-use crate::types::{RequestContext, RequestInfo, RequestMeta};
+use crate::router::{shutdown_response, Router};
+use crate::service::InFlightBody;
+use crate::types::{ConnectionInfo, QueryCache, RequestContext, RequestInfo, RequestMeta, Shutdown};
 use crate::Error;
 use hyper::{body::HttpBody, service::Service, Request, Response};
 use std::future::Future;
-use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-pub struct RequestService<B, E> {
-    pub(crate) router: Arc<Router<B, E>>,
-    pub(crate) remote_addr: SocketAddr,
+pub struct RequestService<B, E, ReqBody = hyper::Body> {
+    pub(crate) router: Arc<Router<B, E, ReqBody>>,
+    pub(crate) connection_info: Arc<ConnectionInfo>,
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    Service<Request<hyper::Body>> for RequestService<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Service<Request<ReqBody>> for RequestService<B, E, ReqBody>
 {
-    type Response = Response<B>;
+    type Response = Response<InFlightBody<B>>;
     type Error = crate::RouteError;
     #[allow(clippy::type_complexity)]
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
@@ -26,12 +29,34 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, mut req: Request<hyper::Body>) -> Self::Future {
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
         let router = self.router.clone();
-        let remote_addr = self.remote_addr;
+        let connection_info = self.connection_info.clone();
+
+        // The new requests are rejected once the shutdown is triggered, they aren't tracked as they don't reach the
+        // route handlers.
+        let rejected = match router.shutdown_body {
+            Some(body) if router.shutdown.is_triggered() => Some(shutdown_response(body)),
+            _ => None,
+        };
+
+        // The request is in flight until the returned future is dropped, or if it succeeds, until the response body is
+        // finished or dropped.
+        let in_flight = match rejected {
+            Some(_) => None,
+            None => Some(router.shutdown.track()),
+        };
 
         let fut = async move {
-            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+            req.extensions_mut().insert(router.shutdown.clone());
+
+            let client_info = router
+                .trusted_proxies
+                .resolve(connection_info.peer_addr(), req.headers());
+            helpers::update_req_meta_in_extensions(
+                req.extensions_mut(),
+                RequestMeta::with_connection(connection_info, client_info),
+            );
 
             let mut target_path = helpers::percent_decode_request_path(req.uri().path())
                 .map_err(|e| Error::new(format!("Couldn't percent decode request path: {}", e)))?;
@@ -52,8 +77,14 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             }
 
             req.extensions_mut().insert(context);
+            let query_cache = QueryCache::new(req.uri());
+            req.extensions_mut().insert(query_cache);
+
+            let res = router
+                .process(target_path.as_str(), req, req_info.clone(), rejected)
+                .await?;
 
-            router.process(target_path.as_str(), req, req_info.clone()).await
+            Ok(res.map(|body| InFlightBody::new(body, in_flight)))
         };
 
         Box::pin(fut)
@@ -61,20 +92,24 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 }
 
 #[derive(Debug)]
-pub struct RequestServiceBuilder<B, E> {
-    router: Arc<Router<B, E>>,
+pub struct RequestServiceBuilder<B, E, ReqBody = hyper::Body> {
+    router: Arc<Router<B, E, ReqBody>>,
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RequestServiceBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RequestServiceBuilder<B, E, ReqBody>
 {
-    pub fn new(mut router: Router<B, E>) -> crate::Result<Self> {
+    pub fn new(mut router: Router<B, E, ReqBody>) -> crate::Result<Self> {
         // router.init_keep_alive_middleware();
 
         router.init_global_options_route();
         router.init_default_404_route();
 
         router.init_err_handler();
+        router.init_shutdown_response();
 
         router.init_regex_set()?;
         router.init_req_info_gen();
@@ -82,32 +117,69 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             router: Arc::from(router),
         })
     }
+}
+
+impl<B, E, ReqBody> Clone for RequestServiceBuilder<B, E, ReqBody> {
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+        }
+    }
+}
+
+impl<B, E, ReqBody> RequestServiceBuilder<B, E, ReqBody> {
+    /// Returns the [shutdown](./struct.Shutdown.html) handle of the router.
+    pub fn shutdown(&self) -> Shutdown {
+        self.router.shutdown.clone()
+    }
 
-    pub fn build(&self, remote_addr: SocketAddr) -> RequestService<B, E> {
+    pub fn build<C: Into<ConnectionInfo>>(&self, connection_info: C) -> RequestService<B, E, ReqBody> {
         RequestService {
             router: self.router.clone(),
-            remote_addr,
+            connection_info: Arc::new(connection_info.into()),
         }
     }
 }
 
+#[cfg(feature = "hyper1")]
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RequestServiceBuilder<B, E, ReqBody>
+{
+    /// Creates a hyper 1.x service for a connection with the provided connection info or remote address.
+    ///
+    /// It's available with the `hyper1` feature, see the [`hyper1`](./hyper1/index.html) module for an example.
+    pub fn build_hyper1<C: Into<ConnectionInfo>>(
+        &self,
+        connection_info: C,
+    ) -> crate::hyper1::RequestService<B, ReqBody> {
+        crate::hyper1::RequestService::new(self.router.clone(), Arc::new(connection_info.into()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Error, RequestServiceBuilder, RouteError, Router};
+    use crate::prelude::*;
+    use crate::{Error, InFlightBody, Middleware, RequestServiceBuilder, RouteError, Router};
     use futures::future::poll_fn;
     use http::Method;
+    use hyper::body::HttpBody;
     use hyper::service::Service;
-    use hyper::{Body, Request, Response};
+    use hyper::{Body, Request, Response, StatusCode};
+    use std::convert::Infallible;
     use std::net::SocketAddr;
+    use std::pin::Pin;
     use std::str::FromStr;
-    use std::task::Poll;
+    use std::task::{Context, Poll};
 
     #[tokio::test]
     async fn should_route_request() {
         const RESPONSE_TEXT: &str = "Hello world!";
         let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
         let router: Router<hyper::body::Body, Error> = Router::builder()
-            .get("/", |_| async move { Ok(Response::new(Body::from(RESPONSE_TEXT))) })
+            .get("/", || async move { Ok(Response::new(Body::from(RESPONSE_TEXT))) })
             .build()
             .unwrap();
         let req = Request::builder()
@@ -120,9 +192,109 @@ mod tests {
         poll_fn(|ctx| -> Poll<Result<(), RouteError>> { service.poll_ready(ctx) })
             .await
             .expect("request service is not ready");
-        let resp: Response<hyper::body::Body> = service.call(req).await.unwrap();
+        let resp: Response<InFlightBody<hyper::body::Body>> = service.call(req).await.unwrap();
         let body = resp.into_body();
         let body = String::from_utf8(hyper::body::to_bytes(body).await.unwrap().to_vec()).unwrap();
         assert_eq!(RESPONSE_TEXT, body)
     }
+
+    #[tokio::test]
+    async fn should_route_request_with_custom_body() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<Body, Error, String> = Router::builder()
+            .middleware(Middleware::pre(|req: Request<String>| async move {
+                Ok(req.map(|body| body.to_uppercase()))
+            }))
+            .post("/users/:name", |req: Request<String>| async move {
+                let greeting = format!("{} {}", req.body(), req.param("name").unwrap());
+                Ok(Response::new(Body::from(greeting)))
+            })
+            .build()
+            .unwrap();
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/users/alice")
+            .body("hello".to_string())
+            .unwrap();
+        let mut service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+        let resp = service.call(req).await.unwrap();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"HELLO alice");
+    }
+
+    #[tokio::test]
+    async fn should_add_defaults_for_custom_response_body() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<String, Error> = Router::builder()
+            .get("/fail", || async move {
+                Err::<Response<String>, _>(Error::new("Something went wrong"))
+            })
+            .build()
+            .unwrap();
+        let mut service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+
+        let req = Request::builder().uri("/unknown").body(Body::empty()).unwrap();
+        let resp = service.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.body().get_ref(), "Not Found");
+
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/fail")
+            .body(Body::empty())
+            .unwrap();
+        let resp = service.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = Request::builder().uri("/fail").body(Body::empty()).unwrap();
+        let resp = service.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            resp.body().get_ref(),
+            "Internal Server Error: routerify::Error: Something went wrong"
+        );
+    }
+
+    // A custom body which can be built from a string.
+    struct TextBody(Option<String>);
+
+    impl From<&'static str> for TextBody {
+        fn from(text: &'static str) -> Self {
+            TextBody(Some(text.to_owned()))
+        }
+    }
+
+    impl From<String> for TextBody {
+        fn from(text: String) -> Self {
+            TextBody(Some(text))
+        }
+    }
+
+    impl HttpBody for TextBody {
+        type Data = hyper::body::Bytes;
+        type Error = Infallible;
+
+        fn poll_data(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+            Poll::Ready(self.0.take().map(|text| Ok(text.into())))
+        }
+
+        fn poll_trailers(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<Option<hyper::HeaderMap>, Self::Error>> {
+            Poll::Ready(Ok(None))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_add_defaults_for_custom_body() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+
+        let router: Router<TextBody, Error> = Router::builder().build().unwrap();
+        let mut service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+        let req = Request::builder().uri("/unknown").body(Body::empty()).unwrap();
+        let resp = service.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.body().get_ref().0.as_deref(), Some("Not Found"));
+    }
 }
@@ -1,18 +1,32 @@
 use crate::router::Router;
+use crate::service::connection::Connection;
 use crate::service::request_service::{RequestService, RequestServiceBuilder};
-use hyper::{body::HttpBody, server::conn::AddrStream, service::Service};
+use crate::types::Shutdown;
+#[cfg(feature = "tower")]
+use crate::{
+    service::InFlightBody,
+    types::{ConnectionInfo, PeerAddr},
+};
+use hyper::{body::HttpBody, service::Service};
+#[cfg(feature = "tower")]
+use hyper::{Request, Response};
 use std::convert::Infallible;
 use std::future::{ready, Ready};
+#[cfg(feature = "tower")]
+use std::net::SocketAddr;
 use std::task::{Context, Poll};
 
 /// A [`Service`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html) to process incoming requests.
 ///
-/// This `RouterService<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterService<B, E, ReqBody>` type accepts three type parameters: `B`, `E` and `ReqBody`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.14.4/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html)
-///   type.
+///   type. The default 404, OPTIONS and error responses are only added if it can be built from a string, please
+///   refer to the [`DefaultBody`](./trait.DefaultBody.html) trait.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqBody` represents the request body type which will be received by route handlers and the pre middlewares. It defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.14.4/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -53,25 +67,40 @@ use std::task::{Context, Poll};
 /// }
 /// ```
 #[derive(Debug)]
-pub struct RouterService<B, E> {
-    builder: RequestServiceBuilder<B, E>,
+pub struct RouterService<B, E, ReqBody = hyper::Body> {
+    builder: RequestServiceBuilder<B, E, ReqBody>,
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RouterService<B, E>
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > RouterService<B, E, ReqBody>
 {
     /// Creates a new service with the provided router and it's ready to be used with the hyper [`serve`](https://docs.rs/hyper/0.14.4/hyper/server/struct.Builder.html#method.serve)
     /// method.
-    pub fn new(router: Router<B, E>) -> crate::Result<RouterService<B, E>> {
+    pub fn new(router: Router<B, E, ReqBody>) -> crate::Result<RouterService<B, E, ReqBody>> {
         let builder = RequestServiceBuilder::new(router)?;
         Ok(RouterService { builder })
     }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    Service<&AddrStream> for RouterService<B, E>
+impl<B, E, ReqBody> RouterService<B, E, ReqBody> {
+    /// Returns the [shutdown](./struct.Shutdown.html) handle of the router, it can be used to trigger the shutdown and
+    /// to wait for the in-flight requests to finish.
+    pub fn shutdown(&self) -> Shutdown {
+        self.builder.shutdown()
+    }
+}
+
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+        C: Connection,
+    > Service<&C> for RouterService<B, E, ReqBody>
 {
-    type Response = RequestService<B, E>;
+    type Response = RequestService<B, E, ReqBody>;
     type Error = Infallible;
     type Future = Ready<Result<Self::Response, Self::Error>>;
 
@@ -79,9 +108,43 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, conn: &AddrStream) -> Self::Future {
-        let req_service = self.builder.build(conn.remote_addr());
+    fn call(&mut self, conn: &C) -> Self::Future {
+        let req_service = self.builder.build(conn.connection_info());
 
         ready(Ok(req_service))
     }
 }
+
+/// With the `tower` feature, a `RouterService` is also a `tower::Service` for the requests themselves, so it can be
+/// wrapped into any `tower` middleware stack.
+///
+/// As there is no connection, the connection info is taken from a [`ConnectionInfo`](./struct.ConnectionInfo.html),
+/// a [`PeerAddr`](./enum.PeerAddr.html) or a [`SocketAddr`](https://doc.rust-lang.org/std/net/enum.SocketAddr.html)
+/// request extension if one is present, otherwise the remote address is [`PeerAddr::Unknown`](./enum.PeerAddr.html#variant.Unknown).
+#[cfg(feature = "tower")]
+impl<
+        B: HttpBody + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        ReqBody: Send + 'static,
+    > Service<Request<ReqBody>> for RouterService<B, E, ReqBody>
+{
+    type Response = Response<InFlightBody<B>>;
+    type Error = crate::RouteError;
+    type Future = <RequestService<B, E, ReqBody> as Service<Request<ReqBody>>>::Future;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let ext = req.extensions();
+        let connection_info = ext
+            .get::<ConnectionInfo>()
+            .cloned()
+            .or_else(|| ext.get::<PeerAddr>().cloned().map(ConnectionInfo::new))
+            .or_else(|| ext.get::<SocketAddr>().copied().map(ConnectionInfo::from))
+            .unwrap_or_else(|| ConnectionInfo::new(PeerAddr::Unknown));
+
+        self.builder.build(connection_info).call(req)
+    }
+}
@@ -0,0 +1,292 @@
+//! Server-Sent Events responses.
+//!
+//! This module is available with the `sse` feature. The [`Sse`](./struct.Sse.html) response takes a `Stream` of
+//! [`Event`](./struct.Event.html)s and sends them with the `text/event-stream` framing, a keep-alive comment is sent
+//! when no event was sent for a while so the proxies don't close the connection. A reconnecting client sends the id of
+//! the last event it received, it's extracted with [`LastEventId`](./struct.LastEventId.html).
+//!
+//! # Examples
+//!
+//! ```
+//! use futures::stream::{self, Stream, StreamExt};
+//! use hyper::Body;
+//! use routerify::sse::{Event, LastEventId, Sse};
+//! use routerify::{RouteError, Router};
+//! use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! fn events(from: u64) -> impl Stream<Item = Result<Event, Infallible>> {
+//!     stream::iter(from..).map(|id| Ok(Event::new().id(id.to_string()).event("tick").data("Hello")))
+//! }
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .get(
+//!         "/events",
+//!         |LastEventId(last_id): LastEventId| async move {
+//!             let from = last_id.and_then(|id| id.parse::<u64>().ok()).map_or(0, |id| id + 1);
+//!             Ok(Sse::new(events(from)).keep_alive(Some(Duration::from_secs(10))))
+//!         },
+//!     )
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::extract::{FromRequestParts, Rejection};
+use crate::response::IntoResponse;
+use crate::RouteError;
+use futures_core::Stream;
+use http::request::Parts;
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Response};
+use std::fmt::Write;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+
+/// An event of a Server-Sent Events stream.
+///
+/// # Panics
+///
+/// The `id`, `event` and `comment` fields can't contain a newline, the methods setting them panic otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Creates an empty event.
+    pub fn new() -> Event {
+        Event::default()
+    }
+
+    /// Sets the id of the event, the client sends the id of the last received event when it reconnects.
+    pub fn id<T: Into<String>>(mut self, id: T) -> Self {
+        self.id = Some(single_line("id", id.into()));
+        self
+    }
+
+    /// Sets the type of the event, the clients listen to the events by their type.
+    pub fn event<T: Into<String>>(mut self, event: T) -> Self {
+        self.event = Some(single_line("event", event.into()));
+        self
+    }
+
+    /// Sets the data of the event, a multi-line data is sent as multiple `data` fields.
+    pub fn data<T: Into<String>>(mut self, data: T) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the data of the event to the value serialized as JSON.
+    ///
+    /// It's available with the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn json_data<T: serde::Serialize>(mut self, data: &T) -> Result<Self, serde_json::Error> {
+        self.data = Some(serde_json::to_string(data)?);
+        Ok(self)
+    }
+
+    /// Sets the time the client should wait before reconnecting.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Sets a comment, the clients ignore it.
+    pub fn comment<T: Into<String>>(mut self, comment: T) -> Self {
+        self.comment = Some(single_line("comment", comment.into()));
+        self
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        let mut buf = String::new();
+
+        if let Some(ref comment) = self.comment {
+            let _ = writeln!(buf, ":{}", comment);
+        }
+        if let Some(ref event) = self.event {
+            let _ = writeln!(buf, "event: {}", event);
+        }
+        if let Some(ref data) = self.data {
+            // The clients end a line at `\r\n`, `\r` or `\n`, so every one of them starts a new `data` field.
+            for line in data.split("\r\n").flat_map(|line| line.split(['\r', '\n'])) {
+                let _ = writeln!(buf, "data: {}", line);
+            }
+        }
+        if let Some(ref id) = self.id {
+            let _ = writeln!(buf, "id: {}", id);
+        }
+        if let Some(retry) = self.retry {
+            let _ = writeln!(buf, "retry: {}", retry.as_millis());
+        }
+        buf.push('\n');
+
+        Bytes::from(buf)
+    }
+}
+
+fn single_line(field: &str, val: String) -> String {
+    assert!(
+        !val.contains(['\n', '\r']),
+        "The `{}` field of an SSE event can't contain a newline",
+        field
+    );
+    val
+}
+
+/// A Server-Sent Events response, it streams the events with the `text/event-stream` content type.
+///
+/// A keep-alive comment is sent every 15 seconds if no event was sent, the interval is set with the
+/// [`keep_alive`](#method.keep_alive) method. If the stream fails, the error is logged and the response is aborted.
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<Duration>,
+}
+
+impl<S> Sse<S> {
+    /// Creates the response from a stream of events.
+    pub fn new(stream: S) -> Sse<S> {
+        Sse {
+            stream,
+            keep_alive: Some(Duration::from_secs(15)),
+        }
+    }
+
+    /// Sets the interval of the keep-alive comments, or disables them with `None`.
+    pub fn keep_alive(mut self, interval: Option<Duration>) -> Self {
+        self.keep_alive = interval;
+        self
+    }
+}
+
+impl<S, E> IntoResponse<Body> for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<RouteError>,
+{
+    fn into_response(self) -> Response<Body> {
+        let body = SseStream {
+            stream: Box::pin(self.stream),
+            keep_alive: self
+                .keep_alive
+                .map(|interval| (interval, Box::pin(tokio::time::sleep(interval)))),
+        };
+
+        let mut res = Response::new(Body::wrap_stream(body));
+        let headers = res.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        res
+    }
+}
+
+// Frames the events and sends the keep-alive comments in between.
+struct SseStream<S> {
+    stream: Pin<Box<S>>,
+    keep_alive: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl<S, E> Stream for SseStream<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+    E: Into<RouteError>,
+{
+    type Item = Result<Bytes, RouteError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        match this.stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(event))) => {
+                if let Some((interval, ref mut sleep)) = this.keep_alive {
+                    sleep.as_mut().reset(Instant::now() + interval);
+                }
+                Poll::Ready(Some(Ok(event.to_bytes())))
+            }
+            Poll::Ready(Some(Err(err))) => {
+                let err = err.into();
+                log::error!("The SSE stream failed: {}", err);
+                Poll::Ready(Some(Err(err)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => match this.keep_alive {
+                Some((interval, ref mut sleep)) => match sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => {
+                        sleep.as_mut().reset(Instant::now() + interval);
+                        Poll::Ready(Some(Ok(Bytes::from_static(b":\n\n"))))
+                    }
+                    Poll::Pending => Poll::Pending,
+                },
+                None => Poll::Pending,
+            },
+        }
+    }
+}
+
+/// Extracts the `Last-Event-ID` header, which a reconnecting client sends with the id of the last event it received.
+///
+/// It's `None` for the first connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastEventId(pub Option<String>);
+
+impl FromRequestParts for LastEventId {
+    fn from_request_parts(parts: &Parts) -> Result<Self, Rejection> {
+        let id = parts
+            .headers
+            .get("last-event-id")
+            .and_then(|val| val.to_str().ok())
+            .map(ToOwned::to_owned);
+
+        Ok(LastEventId(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_framing() {
+        let event = Event::new()
+            .comment(" note")
+            .event("update")
+            .data("first\r\nsecond")
+            .id("7")
+            .retry(Duration::from_secs(3));
+
+        assert_eq!(
+            event.to_bytes(),
+            ": note\nevent: update\ndata: first\ndata: second\nid: 7\nretry: 3000\n\n"
+        );
+        assert_eq!(Event::new().data("").to_bytes(), "data: \n\n");
+    }
+
+    #[test]
+    fn test_event_data_with_carriage_returns() {
+        assert_eq!(
+            Event::new().data("x\revent: admin").to_bytes(),
+            "data: x\ndata: event: admin\n\n"
+        );
+        assert_eq!(
+            Event::new().data("a\r\rb\n\r\nc").to_bytes(),
+            "data: a\ndata: \ndata: b\ndata: \ndata: c\n\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_event_newline_in_id() {
+        let _ = Event::new().id("1\n2");
+    }
+}
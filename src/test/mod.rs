@@ -0,0 +1,232 @@
+//! An in-process test client for routers.
+//!
+//! This module is available with the `test-util` feature. The [`TestClient`](./struct.TestClient.html) sends requests
+//! straight through a [`RequestService`](../struct.RequestService.html), so no socket is bound and no server is
+//! spawned, but the middlewares, the error handlers and the default routes run exactly as they would in a server.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::{Body, Request, Response, StatusCode};
+//! use routerify::prelude::*;
+//! use routerify::test::TestClient;
+//! use routerify::Router;
+//! use std::{convert::Infallible, net::SocketAddr};
+//!
+//! # async fn run() {
+//! let router: Router<Body, Infallible> = Router::builder()
+//!     .get("/users/:userId", |req: Request<Body>| async move {
+//!         let text = format!("User {} from {}", req.param("userId").unwrap(), req.remote_addr());
+//!         Ok(Response::new(Body::from(text)))
+//!     })
+//!     .build()
+//!     .unwrap();
+//!
+//! let client = TestClient::new(router).unwrap().remote_addr("192.0.2.1:4711".parse::<SocketAddr>().unwrap());
+//!
+//! let res = client.get("/users/alice").header("accept", "text/plain").send().await.unwrap();
+//! assert_eq!(res.status(), StatusCode::OK);
+//! assert_eq!(res.text().await.unwrap(), "User alice from 192.0.2.1:4711");
+//! # }
+//! # tokio::runtime::Runtime::new().unwrap().block_on(run());
+//! ```
+
+use crate::service::{InFlightBody, RequestServiceBuilder};
+use crate::types::{ConnectionInfo, PeerAddr};
+use crate::{RouteError, Router};
+use hyper::body::{self, Bytes, HttpBody};
+use hyper::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use hyper::service::Service;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+
+/// A client which sends requests to a router without any network connection.
+///
+/// The requests are sent with an unknown remote address unless one is set via the
+/// [`remote_addr`](#method.remote_addr) or the [`connection_info`](#method.connection_info) method.
+pub struct TestClient<B, E> {
+    builder: RequestServiceBuilder<B, E>,
+    connection_info: ConnectionInfo,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    TestClient<B, E>
+{
+    /// Creates a new test client for the router.
+    pub fn new(router: Router<B, E>) -> crate::Result<TestClient<B, E>> {
+        Ok(TestClient {
+            builder: RequestServiceBuilder::new(router)?,
+            connection_info: ConnectionInfo::new(PeerAddr::Unknown),
+        })
+    }
+
+    /// Sets the remote address the requests seem to come from.
+    pub fn remote_addr<A: Into<PeerAddr>>(self, remote_addr: A) -> TestClient<B, E> {
+        self.connection_info(ConnectionInfo::new(remote_addr.into()))
+    }
+
+    /// Sets the connection info the requests seem to come through e.g. to fake a TLS connection.
+    pub fn connection_info<C: Into<ConnectionInfo>>(mut self, connection_info: C) -> TestClient<B, E> {
+        self.connection_info = connection_info.into();
+        self
+    }
+
+    /// Starts a request with the `GET` method.
+    pub fn get<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::GET, uri)
+    }
+
+    /// Starts a request with the `POST` method.
+    pub fn post<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::POST, uri)
+    }
+
+    /// Starts a request with the `PUT` method.
+    pub fn put<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::PUT, uri)
+    }
+
+    /// Starts a request with the `PATCH` method.
+    pub fn patch<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::PATCH, uri)
+    }
+
+    /// Starts a request with the `DELETE` method.
+    pub fn delete<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// Starts a request with the `HEAD` method.
+    pub fn head<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::HEAD, uri)
+    }
+
+    /// Starts a request with the `OPTIONS` method.
+    pub fn options<U: AsRef<str>>(&self, uri: U) -> TestRequest<'_, B, E> {
+        self.request(Method::OPTIONS, uri)
+    }
+
+    /// Starts a request with the provided method.
+    pub fn request<U: AsRef<str>>(&self, method: Method, uri: U) -> TestRequest<'_, B, E> {
+        TestRequest {
+            client: self,
+            builder: Request::builder().method(method).uri(uri.as_ref()),
+            body: Ok(Body::empty()),
+        }
+    }
+
+    /// Sends a prebuilt request to the router.
+    pub async fn send(&self, req: Request<Body>) -> Result<TestResponse<B>, RouteError> {
+        let mut service = self.builder.build(self.connection_info.clone());
+        let res = service.call(req).await?;
+        Ok(TestResponse { inner: res })
+    }
+}
+
+impl<B, E> Debug for TestClient<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestClient")
+            .field("connection_info", &self.connection_info)
+            .finish()
+    }
+}
+
+/// A request being built by a [`TestClient`](./struct.TestClient.html).
+///
+/// The errors occurred while building the request are returned from the [`send`](#method.send) method.
+pub struct TestRequest<'a, B, E> {
+    client: &'a TestClient<B, E>,
+    builder: http::request::Builder,
+    body: Result<Body, RouteError>,
+}
+
+impl<'a, B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    TestRequest<'a, B, E>
+{
+    /// Appends a header to the request.
+    pub fn header<K, V>(mut self, key: K, value: V) -> TestRequest<'a, B, E>
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// Sets the body of the request.
+    pub fn body<T: Into<Body>>(mut self, body: T) -> TestRequest<'a, B, E> {
+        self.body = Ok(body.into());
+        self
+    }
+
+    /// Sets the body of the request to the value serialized as JSON, and the `Content-Type` header to
+    /// `application/json`.
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> TestRequest<'a, B, E> {
+        self.body = serde_json::to_vec(value).map(Body::from).map_err(Into::into);
+        self.header(CONTENT_TYPE, "application/json")
+    }
+
+    /// Sends the request to the router.
+    pub async fn send(self) -> Result<TestResponse<B>, RouteError> {
+        let req = self.builder.body(self.body?)?;
+        self.client.send(req).await
+    }
+}
+
+impl<'a, B, E> Debug for TestRequest<'a, B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestRequest").field("builder", &self.builder).finish()
+    }
+}
+
+/// A response received by a [`TestClient`](./struct.TestClient.html).
+#[derive(Debug)]
+pub struct TestResponse<B> {
+    inner: Response<InFlightBody<B>>,
+}
+
+impl<B: HttpBody> TestResponse<B>
+where
+    B::Error: Into<RouteError>,
+{
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.inner.status()
+    }
+
+    /// Returns the headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Returns the value of the header as a string, if it's present and it's a valid string.
+    pub fn header<K: AsRef<str>>(&self, key: K) -> Option<&str> {
+        self.inner.headers().get(key.as_ref()).and_then(|val| val.to_str().ok())
+    }
+
+    /// Reads the whole body as bytes.
+    pub async fn bytes(self) -> Result<Bytes, RouteError> {
+        body::to_bytes(self.inner.into_body()).await.map_err(Into::into)
+    }
+
+    /// Reads the whole body as an UTF-8 string.
+    pub async fn text(self) -> Result<String, RouteError> {
+        let bytes = self.bytes().await?;
+        String::from_utf8(bytes.to_vec()).map_err(Into::into)
+    }
+
+    /// Reads the whole body and deserializes it from JSON.
+    pub async fn json<T: DeserializeOwned>(self) -> Result<T, RouteError> {
+        let bytes = self.bytes().await?;
+        serde_json::from_slice(&bytes).map_err(Into::into)
+    }
+
+    /// Returns the underlying response.
+    pub fn into_inner(self) -> Response<InFlightBody<B>> {
+        self.inner
+    }
+}
@@ -0,0 +1,88 @@
+//! Interoperability with the [`tower`](https://docs.rs/tower) ecosystem.
+//!
+//! This module is available with the `tower` feature and it brings three things:
+//!
+//! * [`RouterService`](../struct.RouterService.html) implements `tower::Service<Request<Body>>`, so a whole router
+//!   can be wrapped into any `tower` middleware stack.
+//! * [`RouterBuilder::layer`](../struct.RouterBuilder.html#method.layer) wraps the routes of a router with any
+//!   `tower::Layer` e.g. timeouts, concurrency limits, load shedding or tracing. Wrap a sub-router before mounting
+//!   it with [`scope`](../struct.RouterBuilder.html#method.scope) to apply a layer to that scope only.
+//! * [`RouterBuilder::route_service`](../struct.RouterBuilder.html#method.route_service) uses an arbitrary
+//!   `tower::Service` as a route handler.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::{Body, Method, Response};
+//! use routerify::{RouteError, Router};
+//! use std::time::Duration;
+//! use tower::{service_fn, timeout::TimeoutLayer};
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let metrics = service_fn(|_req| async move { Ok::<_, RouteError>(Response::new(Body::from("metrics"))) });
+//!
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .get("/users", || async move { Ok(Response::new(Body::from("User list"))) })
+//!     // Every route added above gets a timeout of 5 seconds.
+//!     .layer(TimeoutLayer::new(Duration::from_secs(5)))
+//!     .route_service("/metrics/*", vec![Method::GET], metrics)
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::route::Handler;
+use crate::RouteError;
+use hyper::{service::Service, Request, Response};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A [`tower::Service`](https://docs.rs/tower/0.4.13/tower/trait.Service.html) wrapping a route handler.
+///
+/// This is the service a `tower::Layer` receives when it's applied to the routes via
+/// [`RouterBuilder::layer`](../struct.RouterBuilder.html#method.layer).
+pub struct HandlerService<B, ReqBody = hyper::Body> {
+    handler: Arc<Handler<B, ReqBody>>,
+}
+
+impl<B, ReqBody> HandlerService<B, ReqBody> {
+    pub(crate) fn new(handler: Handler<B, ReqBody>) -> HandlerService<B, ReqBody> {
+        HandlerService {
+            handler: Arc::new(handler),
+        }
+    }
+}
+
+impl<B, ReqBody> Clone for HandlerService<B, ReqBody> {
+    fn clone(&self) -> Self {
+        HandlerService {
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<B: 'static, ReqBody> Service<Request<ReqBody>> for HandlerService<B, ReqBody> {
+    type Response = Response<B>;
+    type Error = RouteError;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        (self.handler)(req)
+    }
+}
+
+impl<B, ReqBody> Debug for HandlerService<B, ReqBody> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "HandlerService")
+    }
+}
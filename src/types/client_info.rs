@@ -0,0 +1,98 @@
+use crate::types::PeerAddr;
+
+pub(crate) static UNKNOWN_CLIENT_INFO: ClientInfo = ClientInfo::new(PeerAddr::Unknown);
+
+/// Represents the client which sent the request, as resolved through the trusted proxies.
+///
+/// If the peer of the connection is one of the proxies configured via the
+/// [`RouterBuilder::trusted_proxies`](./struct.RouterBuilder.html#method.trusted_proxies) method, the client address,
+/// the scheme and the host are resolved from the `Forwarded`, `X-Forwarded-For`, `X-Forwarded-Proto`,
+/// `X-Forwarded-Host` and `X-Real-IP` headers. Otherwise, the client address is the peer address itself and the scheme
+/// and the host are not set.
+///
+/// It's accessible via the [`RequestExt::client_info`](./ext/trait.RequestExt.html#method.client_info) method.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use hyper::{Request, Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .trusted_proxies(vec!["10.0.0.0/8", "fd00::/8"])
+///     .get("/", |req: Request<Body>| async move {
+///         let client = req.client_info();
+///         let text = format!(
+///             "Hello {} via {}://{}",
+///             client.addr(),
+///             client.scheme().unwrap_or("http"),
+///             client.host().unwrap_or("localhost")
+///         );
+///
+///         Ok(Response::new(Body::from(text)))
+///      })
+///      .build()
+///      .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    addr: PeerAddr,
+    scheme: Option<String>,
+    host: Option<String>,
+}
+
+impl ClientInfo {
+    pub(crate) const fn new(addr: PeerAddr) -> ClientInfo {
+        ClientInfo {
+            addr,
+            scheme: None,
+            host: None,
+        }
+    }
+
+    pub(crate) fn with_scheme(mut self, scheme: Option<String>) -> ClientInfo {
+        self.scheme = scheme;
+        self
+    }
+
+    pub(crate) fn with_host(mut self, host: Option<String>) -> ClientInfo {
+        self.host = host;
+        self
+    }
+
+    /// Returns the address of the client. It's [`PeerAddr::Unknown`](./enum.PeerAddr.html#variant.Unknown) if a
+    /// trusted proxy forwarded an obfuscated or an `unknown` address.
+    pub fn addr(&self) -> &PeerAddr {
+        &self.addr
+    }
+
+    /// Returns the scheme the client used e.g. `https`, if it's forwarded by a trusted proxy.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Returns the host the client requested, if it's forwarded by a trusted proxy.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+}
+
+/// The forwarding header which is set by the trusted proxies.
+///
+/// It's configured via the [`RouterBuilder::forwarded_header`](./struct.RouterBuilder.html#method.forwarded_header)
+/// method, so the client can't spoof its address via the other headers which the proxies pass through untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// The `Forwarded` header as defined in RFC 7239.
+    Forwarded,
+    /// The `X-Forwarded-For` header, along with the `X-Forwarded-Proto` and `X-Forwarded-Host` headers.
+    XForwardedFor,
+    /// The `X-Real-IP` header.
+    XRealIp,
+}
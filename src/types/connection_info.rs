@@ -0,0 +1,121 @@
+use crate::types::PeerAddr;
+use std::net::SocketAddr;
+
+pub(crate) static UNKNOWN_CONNECTION_INFO: ConnectionInfo = ConnectionInfo::new(PeerAddr::Unknown);
+
+/// Represents some information for the connection which the request came through.
+///
+/// It's filled in by the [`Connection`](./trait.Connection.html) implementation of the accepted stream, and it's
+/// accessible via the [`RequestExt::connection_info`](./ext/trait.RequestExt.html#method.connection_info) and
+/// the [`RequestInfo::connection_info`](./struct.RequestInfo.html#method.connection_info) methods.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use hyper::{Request, Response, Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/internal", |req: Request<Body>| async move {
+///         let conn = req.connection_info();
+///
+///         if conn.peer_certificates().is_none() {
+///             return Ok(Response::builder().status(StatusCode::FORBIDDEN).body(Body::empty()).unwrap());
+///         }
+///
+///         Ok(Response::new(Body::from(format!("Hello {} via {:?}", conn.peer_addr(), conn.server_name()))))
+///      })
+///      .build()
+///      .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    peer_addr: PeerAddr,
+    local_addr: PeerAddr,
+    alpn_protocol: Option<Vec<u8>>,
+    server_name: Option<String>,
+    peer_certificates: Option<Vec<Vec<u8>>>,
+}
+
+impl ConnectionInfo {
+    /// Creates a new connection info with the address of the peer, the other fields are empty.
+    pub const fn new(peer_addr: PeerAddr) -> ConnectionInfo {
+        ConnectionInfo {
+            peer_addr,
+            local_addr: PeerAddr::Unknown,
+            alpn_protocol: None,
+            server_name: None,
+            peer_certificates: None,
+        }
+    }
+
+    /// Sets the local address of the connection.
+    pub fn with_local_addr(mut self, local_addr: PeerAddr) -> ConnectionInfo {
+        self.local_addr = local_addr;
+        self
+    }
+
+    /// Sets the protocol negotiated via TLS ALPN.
+    pub fn with_alpn_protocol(mut self, alpn_protocol: Vec<u8>) -> ConnectionInfo {
+        self.alpn_protocol = Some(alpn_protocol);
+        self
+    }
+
+    /// Sets the hostname sent by the client via TLS SNI.
+    pub fn with_server_name<N: Into<String>>(mut self, server_name: N) -> ConnectionInfo {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    /// Sets the DER-encoded certificate chain presented by the client, the end-entity certificate comes first.
+    pub fn with_peer_certificates(mut self, peer_certificates: Vec<Vec<u8>>) -> ConnectionInfo {
+        self.peer_certificates = Some(peer_certificates);
+        self
+    }
+
+    /// Returns the address of the peer.
+    pub fn peer_addr(&self) -> &PeerAddr {
+        &self.peer_addr
+    }
+
+    /// Returns the local address of the connection. It's [`PeerAddr::Unknown`](./enum.PeerAddr.html#variant.Unknown)
+    /// if the connection doesn't provide one.
+    pub fn local_addr(&self) -> &PeerAddr {
+        &self.local_addr
+    }
+
+    /// Returns the protocol negotiated via TLS ALPN e.g. `b"h2"`.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the hostname sent by the client via TLS SNI.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// Returns the DER-encoded certificate chain presented by the client, the end-entity certificate comes first.
+    ///
+    /// It's `None` if the connection isn't a TLS one or the client didn't present a certificate.
+    pub fn peer_certificates(&self) -> Option<&[Vec<u8>]> {
+        self.peer_certificates.as_deref()
+    }
+}
+
+impl From<PeerAddr> for ConnectionInfo {
+    fn from(peer_addr: PeerAddr) -> Self {
+        ConnectionInfo::new(peer_addr)
+    }
+}
+
+impl From<SocketAddr> for ConnectionInfo {
+    fn from(peer_addr: SocketAddr) -> Self {
+        ConnectionInfo::new(PeerAddr::Tcp(peer_addr))
+    }
+}
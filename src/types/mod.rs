@@ -1,9 +1,23 @@
+pub(crate) use client_info::UNKNOWN_CLIENT_INFO;
+pub use client_info::{ClientInfo, ForwardedHeader};
+pub use connection_info::ConnectionInfo;
+pub(crate) use connection_info::UNKNOWN_CONNECTION_INFO;
+pub use peer_addr::PeerAddr;
+pub(crate) use query_params::QueryCache;
+pub use query_params::{QueryError, QueryParams};
 pub(crate) use request_context::RequestContext;
 pub use request_info::RequestInfo;
 pub(crate) use request_meta::RequestMeta;
-pub use route_params::RouteParams;
+pub use route_params::{ParamError, RouteParams};
+pub(crate) use shutdown::InFlight;
+pub use shutdown::Shutdown;
 
+mod client_info;
+mod connection_info;
+mod peer_addr;
+mod query_params;
 mod request_context;
 mod request_info;
 mod request_meta;
 mod route_params;
+mod shutdown;
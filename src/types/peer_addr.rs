@@ -0,0 +1,70 @@
+use std::fmt::{self, Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Represents the address of the peer which sent the request.
+///
+/// It's returned by the [`RequestExt::remote_addr`](./ext/trait.RequestExt.html#method.remote_addr) method and depends
+/// on the connection type the [`RouterService`](./struct.RouterService.html) is served over.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, PeerAddr};
+/// use routerify::ext::RequestExt;
+/// use hyper::{Request, Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/", |req: Request<Body>| async move {
+///         let text = match req.remote_addr() {
+///             PeerAddr::Tcp(addr) => format!("Hello from TCP address: {}", addr.ip()),
+///             PeerAddr::Unix(_) => "Hello from Unix socket".to_string(),
+///             PeerAddr::Unknown => "Hello stranger".to_string(),
+///         };
+///
+///         Ok(Response::new(Body::from(text)))
+///      })
+///      .build()
+///      .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PeerAddr {
+    /// The socket address of a TCP peer.
+    Tcp(SocketAddr),
+    /// The path of a Unix domain socket peer, it's `None` for an unnamed socket which is the usual case for clients.
+    Unix(Option<PathBuf>),
+    /// The peer address is not known e.g. for an in-memory connection.
+    Unknown,
+}
+
+impl PeerAddr {
+    /// Returns the socket address if the peer is connected over TCP.
+    pub fn as_tcp(&self) -> Option<SocketAddr> {
+        match self {
+            PeerAddr::Tcp(addr) => Some(*addr),
+            _ => None,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> Self {
+        PeerAddr::Tcp(addr)
+    }
+}
+
+impl Display for PeerAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerAddr::Tcp(addr) => write!(f, "{}", addr),
+            PeerAddr::Unix(Some(path)) => write!(f, "unix:{}", path.display()),
+            PeerAddr::Unix(None) => write!(f, "unix:(unnamed)"),
+            PeerAddr::Unknown => write!(f, "unknown"),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use hyper::{StatusCode, Uri};
+use percent_encoding::percent_decode_str;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Represents the query string parameters of a request as a multi-map, the names and the values are percent-decoded
+/// and they're kept in the order of the query string.
+///
+/// It's parsed once per request and cached in the request extensions, it can be accessed as `req.query_params()` from
+/// the route handlers and the pre middlewares, and as `req_info.query_params()` from the post middlewares and the
+/// error handlers.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, RouteError};
+/// use routerify::ext::RequestExt;
+/// use hyper::{Request, Response, Body};
+///
+/// # fn run() -> Router<Body, RouteError> {
+/// let router = Router::builder()
+///     .get("/books", |req: Request<Body>| async move {
+///         let page = req.query_params().parse::<u32>("page")?.unwrap_or(1);
+///         let tags = req.query_all("tag");
+///         let sort = req.query("sort").unwrap_or("title");
+///
+///         Ok(Response::new(Body::from(format!("Page {} of {:?} sorted by {}", page, tags, sort))))
+///      })
+///      .build()
+///      .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryParams(Vec<(String, String)>);
+
+impl QueryParams {
+    /// Creates an empty query parameters map.
+    pub fn new() -> QueryParams {
+        QueryParams(Vec::new())
+    }
+
+    /// Parses the query string without the leading `?`. The `+` characters are decoded as spaces and the invalid
+    /// UTF-8 sequences are replaced.
+    pub fn from_query(query: &str) -> QueryParams {
+        let params = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, val) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode(name), decode(val))
+            })
+            .collect();
+
+        QueryParams(params)
+    }
+
+    /// Returns the first value of the query parameter with the specified name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, val)| val.as_str())
+    }
+
+    /// Returns all the values of the query parameter with the specified name e.g. for `?tag=a&tag=b`.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(param_name, _)| param_name == name)
+            .map(|(_, val)| val.as_str())
+            .collect()
+    }
+
+    /// Checks if a query parameter exists.
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Parses the first value of the query parameter with the specified name into any type which implements `FromStr`.
+    ///
+    /// It's `Ok(None)` if the parameter doesn't exist, and it fails with a [`QueryError`](./struct.QueryError.html)
+    /// if the value couldn't be parsed. Once converted into a [`Rejection`](./extract/struct.Rejection.html), the
+    /// default error handler of the router responds with `400 Bad Request` to it.
+    pub fn parse<T>(&self, name: &str) -> Result<Option<T>, QueryError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(name).map(|val| parse_value(name, val)).transpose()
+    }
+
+    /// Parses all the values of the query parameter with the specified name into any type which implements `FromStr`.
+    pub fn parse_all<T>(&self, name: &str) -> Result<Vec<T>, QueryError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get_all(name)
+            .into_iter()
+            .map(|val| parse_value(name, val))
+            .collect()
+    }
+
+    /// Returns the number of the query parameters, counting the repeated names.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter entries
+    /// as `(name, value)` in the order of the query string.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(name, val)| (name.as_str(), val.as_str()))
+    }
+}
+
+fn decode(val: &str) -> String {
+    let val = val.replace('+', " ");
+    percent_decode_str(&val).decode_utf8_lossy().into_owned()
+}
+
+fn parse_value<T>(name: &str, val: &str) -> Result<T, QueryError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    val.parse().map_err(|e: T::Err| QueryError {
+        name: name.to_owned(),
+        value: val.to_owned(),
+        msg: e.to_string(),
+    })
+}
+
+/// The error returned when a query parameter couldn't be parsed.
+///
+/// The default error handler of the router responds with `400 Bad Request` to it, as it does once it's converted into a
+/// [`Rejection`](./extract/struct.Rejection.html). Otherwise, a custom error handler can downcast the
+/// [`RouteError`](./type.RouteError.html) to it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct QueryError {
+    name: String,
+    value: String,
+    msg: String,
+}
+
+impl QueryError {
+    /// Returns the name of the query parameter.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the value of the query parameter.
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    /// Returns the status of the response which should be sent.
+    pub fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query parameter `{}`: {}", self.name, self.msg)
+    }
+}
+
+impl Debug for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "routerify::QueryError: {}", self)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+// Caches the query parameters of the request in its extensions, they're parsed on the first access.
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    query: Option<String>,
+    params: OnceLock<QueryParams>,
+}
+
+impl QueryCache {
+    pub(crate) fn new(uri: &Uri) -> QueryCache {
+        QueryCache {
+            query: uri.query().map(ToOwned::to_owned),
+            params: OnceLock::new(),
+        }
+    }
+
+    // Checks if the cache is still valid, e.g. a middleware could have rewritten the uri.
+    pub(crate) fn is_valid_for(&self, uri: &Uri) -> bool {
+        self.query.as_deref() == uri.query()
+    }
+
+    pub(crate) fn params(&self) -> &QueryParams {
+        self.params
+            .get_or_init(|| QueryParams::from_query(self.query.as_deref().unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_query() {
+        let params = QueryParams::from_query("tag=a%20b&page=2&tag=c+d&&empty&name=%E2%9C%93");
+        assert_eq!(params.get("page"), Some("2"));
+        assert_eq!(params.get_all("tag"), vec!["a b", "c d"]);
+        assert_eq!(params.get("empty"), Some(""));
+        assert_eq!(params.get("name"), Some("✓"));
+        assert_eq!(params.get("missing"), None);
+        assert_eq!(params.len(), 5);
+
+        assert_eq!(params.parse::<u32>("page"), Ok(Some(2)));
+        assert_eq!(params.parse::<u32>("missing"), Ok(None));
+        assert_eq!(
+            params.parse_all::<u32>("tag").unwrap_err().to_string(),
+            "Invalid query parameter `tag`: invalid digit found in string"
+        );
+
+        assert!(QueryParams::from_query("").is_empty());
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let uri: Uri = "/books?page=2".parse().unwrap();
+        let cache = QueryCache::new(&uri);
+        assert!(cache.is_valid_for(&uri));
+        assert!(!cache.is_valid_for(&"/books?page=3".parse().unwrap()));
+        assert_eq!(cache.params().get("page"), Some("2"));
+    }
+}
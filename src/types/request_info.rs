@@ -1,6 +1,6 @@
-use super::RequestContext;
+use super::{ConnectionInfo, QueryCache, QueryParams, RequestContext, RequestMeta, UNKNOWN_CONNECTION_INFO};
 use crate::data_map::SharedDataMap;
-use hyper::{Body, HeaderMap, Method, Request, Uri, Version};
+use hyper::{HeaderMap, Method, Request, Uri, Version};
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
 
@@ -21,15 +21,23 @@ pub(crate) struct RequestInfoInner {
     method: Method,
     uri: Uri,
     version: Version,
+    connection_info: Option<Arc<ConnectionInfo>>,
+    query_cache: QueryCache,
 }
 
 impl RequestInfo {
-    pub(crate) fn new_from_req(req: &Request<Body>, ctx: RequestContext) -> Self {
+    pub(crate) fn new_from_req<T>(req: &Request<T>, ctx: RequestContext) -> Self {
         let inner = RequestInfoInner {
             headers: req.headers().clone(),
             method: req.method().clone(),
             uri: req.uri().clone(),
             version: req.version(),
+            connection_info: req
+                .extensions()
+                .get::<RequestMeta>()
+                .and_then(|meta| meta.connection_info())
+                .cloned(),
+            query_cache: QueryCache::new(req.uri()),
         };
 
         RequestInfo {
@@ -54,11 +62,38 @@ impl RequestInfo {
         &self.req_info_inner.uri
     }
 
+    /// Returns the percent-decoded query string parameters of the request, they're parsed on the first access.
+    ///
+    /// Please refer to the [QueryParams](./struct.QueryParams.html) for more info.
+    pub fn query_params(&self) -> &QueryParams {
+        self.req_info_inner.query_cache.params()
+    }
+
+    /// Returns the first value of the query string parameter by its name.
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query_params().get(name)
+    }
+
+    /// Returns all the values of the query string parameter by its name e.g. for `?tag=a&tag=b`.
+    pub fn query_all(&self, name: &str) -> Vec<&str> {
+        self.query_params().get_all(name)
+    }
+
     /// Returns the request's HTTP version.
     pub fn version(&self) -> Version {
         self.req_info_inner.version
     }
 
+    /// Returns the information of the connection which the request came through.
+    ///
+    /// Please refer to the [ConnectionInfo](./struct.ConnectionInfo.html) for more info.
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        self.req_info_inner
+            .connection_info
+            .as_deref()
+            .unwrap_or(&UNKNOWN_CONNECTION_INFO)
+    }
+
     /// Access data which was shared by the [`RouterBuilder`](./struct.RouterBuilder.html) method
     /// [`data`](./struct.RouterBuilder.html#method.data).
     ///
@@ -98,7 +133,7 @@ impl RequestInfo {
     ///
     ///         Ok(res)
     ///     }))
-    ///     .get("/hello", |req| async move {
+    ///     .get("/hello", |req: Request<Body>| async move {
     ///         let text = req.context::<String>().unwrap();
     ///
     ///         Ok(Response::new(Body::from(format!("Hello from : {}", text))))
@@ -1,24 +1,27 @@
-use crate::types::route_params::RouteParams;
-use std::net::SocketAddr;
+use crate::types::{ClientInfo, ConnectionInfo, RouteParams};
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub(crate) struct RequestMeta {
     route_params: Option<RouteParams>,
-    remote_addr: Option<SocketAddr>,
+    connection_info: Option<Arc<ConnectionInfo>>,
+    client_info: Option<ClientInfo>,
 }
 
 impl RequestMeta {
     pub fn with_route_params(route_params: RouteParams) -> RequestMeta {
         RequestMeta {
             route_params: Some(route_params),
-            remote_addr: None,
+            connection_info: None,
+            client_info: None,
         }
     }
 
-    pub fn with_remote_addr(remote_addr: SocketAddr) -> RequestMeta {
+    pub fn with_connection(connection_info: Arc<ConnectionInfo>, client_info: ClientInfo) -> RequestMeta {
         RequestMeta {
             route_params: None,
-            remote_addr: Some(remote_addr),
+            connection_info: Some(connection_info),
+            client_info: Some(client_info),
         }
     }
 
@@ -26,13 +29,21 @@ impl RequestMeta {
         self.route_params.as_ref()
     }
 
-    pub fn remote_addr(&self) -> Option<&SocketAddr> {
-        self.remote_addr.as_ref()
+    pub fn connection_info(&self) -> Option<&Arc<ConnectionInfo>> {
+        self.connection_info.as_ref()
+    }
+
+    pub fn client_info(&self) -> Option<&ClientInfo> {
+        self.client_info.as_ref()
     }
 
     pub fn extend(&mut self, other_req_meta: RequestMeta) {
-        if let Some(other_ra) = other_req_meta.remote_addr {
-            self.remote_addr = Some(other_ra)
+        if let Some(other_ci) = other_req_meta.connection_info {
+            self.connection_info = Some(other_ci)
+        }
+
+        if let Some(other_cl) = other_req_meta.client_info {
+            self.client_info = Some(other_cl)
         }
 
         if let Some(other_pm) = other_req_meta.route_params {
@@ -0,0 +1,334 @@
+use hyper::StatusCode;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+mod de;
+
+/// Represents a map of the route parameters using the name of the parameter specified in the path as their respective keys.
+///
+/// Please refer to the [Route Parameters](./index.html#route-parameters) section for more info.
+///
+/// **Note:** This type shouldn't be created directly. It will be populated into the `req` object of the route handler and
+/// can be accessed as `req.params()`.
+#[derive(Debug, Clone, Default)]
+pub struct RouteParams(Vec<(String, String)>);
+
+impl RouteParams {
+    /// Creates an empty route parameters map.
+    pub fn new() -> RouteParams {
+        RouteParams(Vec::new())
+    }
+
+    /// Creates an empty route parameters map with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> RouteParams {
+        RouteParams(Vec::with_capacity(capacity))
+    }
+
+    /// Sets a new parameter entry with the specified key and the value.
+    pub fn set<N: Into<String>, V: Into<String>>(&mut self, param_name: N, param_val: V) {
+        let param_name = param_name.into();
+        let param_val = param_val.into();
+
+        match self.0.iter_mut().find(|(name, _)| *name == param_name) {
+            Some((_, val)) => *val = param_val,
+            None => self.0.push((param_name, param_val)),
+        }
+    }
+
+    /// Returns the route parameter value mapped with the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteParams};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userName/books/:bookName", |req: Request<Body>| async move {
+    ///         let params: &RouteParams = req.params();
+    ///         
+    ///         let user_name = params.get("userName").unwrap();
+    ///         let book_name = params.get("bookName").unwrap();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Username: {}, Book Name: {}", user_name, book_name))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get<N: Into<String>>(&self, param_name: N) -> Option<&String> {
+        let param_name = param_name.into();
+        self.0.iter().find(|(name, _)| *name == param_name).map(|(_, val)| val)
+    }
+
+    /// Parses the route parameter mapped with the specified key into any type which implements `FromStr`.
+    ///
+    /// It fails with a [`ParamError`](./enum.ParamError.html) if the parameter doesn't exist or it couldn't be parsed.
+    /// Once converted into a [`Rejection`](./extract/struct.Rejection.html), the default error handler of the router
+    /// responds with the [`status`](./enum.ParamError.html#method.status) of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteError};
+    /// use routerify::ext::RequestExt;
+    /// use routerify::extract::Rejection;
+    /// use hyper::{Request, Response, Body};
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userId", |req: Request<Body>| async move {
+    ///         let user_id = req.params().parse::<u64>("userId").map_err(Rejection::from)?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("User ID: {}", user_id))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn parse<T>(&self, param_name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let val = match self.get(param_name) {
+            Some(val) => val,
+            None => return Err(ParamError::Missing(param_name.to_owned())),
+        };
+
+        val.parse().map_err(|e: T::Err| ParamError::Invalid {
+            name: param_name.to_owned(),
+            value: val.clone(),
+            msg: e.to_string(),
+        })
+    }
+
+    /// Deserializes all the route parameters into a struct, a map or a tuple with `serde`.
+    ///
+    /// A struct or a map is built from the parameter names, a tuple or a sequence from the parameters in the order of
+    /// the path, and a single value from the only parameter. The values are parsed from the parameters according to
+    /// the types of the fields.
+    ///
+    /// It fails with a [`ParamError`](./enum.ParamError.html) if a parameter doesn't exist or it couldn't be
+    /// deserialized. It's available with the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteError};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct BookParams {
+    ///     #[serde(rename = "userId")]
+    ///     user_id: u64,
+    ///     #[serde(rename = "bookName")]
+    ///     book_name: String,
+    /// }
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userId/books/:bookName", |req: Request<Body>| async move {
+    ///         let params: BookParams = req.params().deserialize()?;
+    ///         let (user_id, book_name): (u64, String) = req.params().deserialize()?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("User ID: {}, Book Name: {}", params.user_id, book_name))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, T: serde::Deserialize<'de>>(&'de self) -> Result<T, ParamError> {
+        T::deserialize(de::ParamsDeserializer::new(&self.0))
+    }
+
+    /// Checks if a route parameter exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteParams};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Request, Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userName", |req: Request<Body>| async move {
+    ///         let params: &RouteParams = req.params();
+    ///         
+    ///         if params.has("userName") {
+    ///             Ok(Response::new(Body::from(params.get("userName").unwrap().to_string())))
+    ///         } else {
+    ///             Ok(Response::new(Body::from("username is not provided")))
+    ///         }
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn has<N: Into<String>>(&self, param_name: N) -> bool {
+        self.get(param_name).is_some()
+    }
+
+    /// Returns the length of the route parameters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter names in
+    /// the order of the path.
+    pub fn params_names(&self) -> impl Iterator<Item = &String> {
+        self.0.iter().map(|(name, _)| name)
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter entries
+    /// as `(parameter_name: &String, parameter_value:  &String)` in the order of the path.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter().map(|(name, val)| (name, val))
+    }
+
+    /// Extends the current parameters map with other one.
+    pub fn extend(&mut self, other_route_params: RouteParams) {
+        other_route_params.0.into_iter().for_each(|(key, val)| {
+            self.set(key, val);
+        })
+    }
+}
+
+/// The error returned when the route parameters couldn't be parsed or deserialized.
+///
+/// The default error handler of the router responds with its [`status`](#method.status), as it does once it's converted
+/// into a [`Rejection`](./extract/struct.Rejection.html). Otherwise, a custom error handler can downcast the
+/// [`RouteError`](./type.RouteError.html) to it.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// The route parameter with this name doesn't exist.
+    Missing(String),
+    /// The route parameter couldn't be parsed.
+    Invalid {
+        /// The name of the route parameter.
+        name: String,
+        /// The value of the route parameter.
+        value: String,
+        /// The message describing the failure.
+        msg: String,
+    },
+    /// The route parameters couldn't be deserialized into the requested type.
+    Deserialize(String),
+}
+
+impl ParamError {
+    /// Returns the status of the response which should be sent.
+    ///
+    /// It's `404 Not Found` for a missing parameter, as the path didn't match the expected resource, and
+    /// `400 Bad Request` otherwise.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ParamError::Missing(_) => StatusCode::NOT_FOUND,
+            ParamError::Invalid { .. } | ParamError::Deserialize(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Missing(name) => write!(f, "Missing route parameter `{}`", name),
+            ParamError::Invalid { name, msg, .. } => write!(f, "Invalid route parameter `{}`: {}", name, msg),
+            ParamError::Deserialize(msg) => write!(f, "Couldn't deserialize the route parameters: {}", msg),
+        }
+    }
+}
+
+impl Debug for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "routerify::ParamError: {}", self)
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> RouteParams {
+        let mut params = RouteParams::new();
+        params.set("userId", "42");
+        params.set("bookName", "rust");
+        params
+    }
+
+    #[test]
+    fn test_parse() {
+        let params = params();
+        assert_eq!(params.parse::<u64>("userId"), Ok(42));
+        assert_eq!(params.parse::<String>("bookName"), Ok("rust".to_owned()));
+        assert_eq!(
+            params.parse::<u64>("pageId"),
+            Err(ParamError::Missing("pageId".to_owned()))
+        );
+
+        let err = params.parse::<u64>("bookName").unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            err.to_string(),
+            "Invalid route parameter `bookName`: invalid digit found in string"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            user_id: u64,
+            book_name: String,
+            page: Option<u32>,
+        }
+
+        let params = params();
+        assert_eq!(
+            params.deserialize::<Params>(),
+            Ok(Params {
+                user_id: 42,
+                book_name: "rust".to_owned(),
+                page: None
+            })
+        );
+        assert_eq!(params.deserialize::<(u64, &str)>(), Ok((42, "rust")));
+
+        let err = params.deserialize::<(String, u64)>().unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert!(matches!(err, ParamError::Invalid { ref name, .. } if name == "bookName"));
+
+        let mut params = RouteParams::new();
+        params.set("userId", "42");
+        assert_eq!(params.deserialize::<u64>(), Ok(42));
+        assert_eq!(
+            params.deserialize::<Params>(),
+            Err(ParamError::Missing("bookName".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// A handle to the shutdown state of a router, it's shared by the services created from the same router.
+///
+/// It's accessible via the [`RouterService::shutdown`](./struct.RouterService.html#method.shutdown) method and from the
+/// handlers and the middlewares via the [`RequestExt::shutdown`](./ext/trait.RequestExt.html#method.shutdown) method.
+/// Once it's [triggered](#method.trigger), the handlers can observe it to finish their work early, and the new requests
+/// are answered with `503 Service Unavailable` if the router is built with
+/// [`RouterBuilder::reject_during_shutdown`](./struct.RouterBuilder.html#method.reject_during_shutdown).
+///
+/// # Examples
+///
+/// ```no_run
+/// use hyper::{Body, Response, Server};
+/// use routerify::{Router, RouterService};
+/// use std::{convert::Infallible, net::SocketAddr, time::Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let router: Router<Body, Infallible> = Router::builder()
+///         .reject_during_shutdown(true)
+///         .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+///         .build()
+///         .unwrap();
+///
+///     let service = RouterService::new(router).unwrap();
+///     let shutdown = service.shutdown();
+///
+///     let signal = shutdown.clone();
+///     tokio::spawn(async move {
+///         tokio::signal::ctrl_c().await.unwrap();
+///         signal.trigger();
+///     });
+///
+///     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+///     let server = Server::bind(&addr).serve(service).with_graceful_shutdown(shutdown.triggered());
+///
+///     // Exit when the server has stopped or, at the latest, 30 seconds after the shutdown is triggered.
+///     tokio::select! {
+///         _ = server => {}
+///         drained = shutdown.drain(Duration::from_secs(30)) => {
+///             if !drained {
+///                 eprintln!("{} requests are still in flight", shutdown.in_flight());
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<ShutdownInner>,
+}
+
+#[derive(Debug, Default)]
+struct ShutdownInner {
+    triggered: AtomicBool,
+    triggered_notify: Notify,
+    in_flight: AtomicUsize,
+    idle_notify: Notify,
+}
+
+impl Shutdown {
+    pub(crate) fn new() -> Shutdown {
+        Shutdown::default()
+    }
+
+    /// Triggers the shutdown. Calling it more than once has no effect.
+    pub fn trigger(&self) {
+        if !self.inner.triggered.swap(true, Ordering::SeqCst) {
+            log::debug!("Shutdown triggered with {} requests in flight", self.in_flight());
+            self.inner.triggered_notify.notify_waiters();
+        }
+    }
+
+    /// Returns `true` if the shutdown is triggered.
+    pub fn is_triggered(&self) -> bool {
+        self.inner.triggered.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the shutdown is triggered.
+    ///
+    /// It can be passed to the hyper [`with_graceful_shutdown`](https://docs.rs/hyper/0.14.4/hyper/server/struct.Server.html#method.with_graceful_shutdown)
+    /// method to stop accepting new connections.
+    pub async fn triggered(&self) {
+        loop {
+            let notified = self.inner.triggered_notify.notified();
+            if self.is_triggered() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Returns the number of the requests which are being processed by the router, including the ones whose response
+    /// body is still being sent.
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the shutdown is triggered and then until the in-flight requests are finished, but no longer than
+    /// the deadline after the shutdown is triggered.
+    ///
+    /// It returns `true` if all the in-flight requests are finished, and `false` if the deadline is reached.
+    pub async fn drain(&self, deadline: Duration) -> bool {
+        self.triggered().await;
+
+        let idle = async {
+            loop {
+                let notified = self.inner.idle_notify.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(deadline, idle).await.is_ok()
+    }
+
+    // Counts a request as in-flight until the returned guard is dropped.
+    pub(crate) fn track(&self) -> InFlight {
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight { shutdown: self.clone() }
+    }
+}
+
+pub(crate) struct InFlight {
+    shutdown: Shutdown,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.shutdown.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shutdown.inner.idle_notify.notify_waiters();
+        }
+    }
+}
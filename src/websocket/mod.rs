@@ -0,0 +1,313 @@
+//! WebSocket upgrades for the route handlers.
+//!
+//! This module is available with the `websocket` feature. The [`WebSocketUpgrade`](./struct.WebSocketUpgrade.html)
+//! extractor validates the upgrade headers of the request, and its [`on_upgrade`](./struct.WebSocketUpgrade.html#method.on_upgrade)
+//! method returns the `101 Switching Protocols` response. The response goes through the post middlewares as usual, and
+//! once the connection is upgraded the callback gets a [`WebSocket`](./struct.WebSocket.html) to receive and send the
+//! messages.
+//!
+//! The requests which aren't valid WebSocket upgrades are rejected with `400 Bad Request`, or with
+//! `405 Method Not Allowed` if their method isn't `GET`.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyper::Body;
+//! use routerify::websocket::{Message, WebSocketUpgrade};
+//! use routerify::{RouteError, Router};
+//!
+//! # fn run() -> Router<Body, RouteError> {
+//! let router: Router<Body, RouteError> = Router::builder()
+//!     .get(
+//!         "/echo",
+//!         |ws: WebSocketUpgrade| async move {
+//!             Ok(ws.on_upgrade(|mut socket| async move {
+//!                 while let Some(Ok(msg)) = socket.recv().await {
+//!                     if let Message::Text(text) = msg {
+//!                         if socket.send(Message::text(text)).await.is_err() {
+//!                             break;
+//!                         }
+//!                     }
+//!                 }
+//!             }))
+//!         },
+//!     )
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::extract::{ExtractFuture, FromRequest, Rejection};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+use hyper::{Method, Request, Response, StatusCode};
+use sha1::{Digest, Sha1};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::WebSocketStream;
+
+pub use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+pub use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+pub use tokio_tungstenite::tungstenite::{Error as WebSocketError, Message};
+
+// The GUID which is appended to the key of the client, as specified by RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Extracts a WebSocket upgrade request, it's used to respond with the upgrade and to handle the connection after.
+///
+/// Please refer to the [module documentation](./index.html) for an example.
+pub struct WebSocketUpgrade {
+    accept: HeaderValue,
+    requested_protocols: Vec<String>,
+    protocol: Option<HeaderValue>,
+    config: Option<WebSocketConfig>,
+    on_upgrade: OnUpgrade,
+}
+
+impl WebSocketUpgrade {
+    /// Validates the upgrade headers of a request and takes over its upgrade, so it can be used from a route handler
+    /// which takes the whole request.
+    pub fn new<ReqBody>(req: &mut Request<ReqBody>) -> Result<WebSocketUpgrade, Rejection> {
+        if req.method() != Method::GET {
+            return Err(Rejection::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "A WebSocket upgrade request must use the `GET` method",
+            ));
+        }
+
+        let headers = req.headers();
+        if !has_token(headers, header::CONNECTION, "upgrade") || !has_token(headers, header::UPGRADE, "websocket") {
+            return Err(Rejection::new(
+                StatusCode::BAD_REQUEST,
+                "Expected the `Connection: upgrade` and `Upgrade: websocket` headers",
+            ));
+        }
+
+        if headers.get(header::SEC_WEBSOCKET_VERSION).map(|val| val.as_bytes()) != Some(b"13") {
+            return Err(Rejection::new(
+                StatusCode::BAD_REQUEST,
+                "Expected the `Sec-WebSocket-Version: 13` header",
+            ));
+        }
+
+        let accept = match headers.get(header::SEC_WEBSOCKET_KEY) {
+            Some(key) => accept_key(key.as_bytes()),
+            None => {
+                return Err(Rejection::new(
+                    StatusCode::BAD_REQUEST,
+                    "Expected the `Sec-WebSocket-Key` header",
+                ))
+            }
+        };
+
+        let requested_protocols = headers
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .map(|protocol| protocol.trim().to_owned())
+            .filter(|protocol| !protocol.is_empty())
+            .collect();
+
+        Ok(WebSocketUpgrade {
+            accept,
+            requested_protocols,
+            protocol: None,
+            config: None,
+            on_upgrade: hyper::upgrade::on(req),
+        })
+    }
+
+    /// Returns the subprotocols requested by the client via the `Sec-WebSocket-Protocol` header.
+    pub fn requested_protocols(&self) -> &[String] {
+        self.requested_protocols.as_slice()
+    }
+
+    /// Selects the first of the specified subprotocols which the client requested, it's sent back in the
+    /// `Sec-WebSocket-Protocol` header.
+    pub fn protocols<I, P>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        self.protocol = protocols
+            .into_iter()
+            .find(|protocol| self.requested_protocols.iter().any(|p| p == protocol.as_ref()))
+            .and_then(|protocol| HeaderValue::from_str(protocol.as_ref()).ok());
+        self
+    }
+
+    /// Sets the config of the WebSocket e.g. the maximum message size.
+    pub fn config(mut self, config: WebSocketConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Returns the `101 Switching Protocols` response which should be returned from the route handler, and calls the
+    /// callback with the [`WebSocket`](./struct.WebSocket.html) once the connection is upgraded.
+    ///
+    /// The callback is spawned onto the tokio runtime. If the upgrade fails e.g. the client closed the connection, the
+    /// error is logged and the callback isn't called.
+    pub fn on_upgrade<B, F, Fut>(self, callback: F) -> Response<B>
+    where
+        B: From<&'static str>,
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let WebSocketUpgrade {
+            accept,
+            protocol,
+            config,
+            on_upgrade,
+            ..
+        } = self;
+
+        let mut res = Response::new(B::from(""));
+        *res.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+
+        let headers = res.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(header::SEC_WEBSOCKET_ACCEPT, accept);
+        if let Some(ref protocol) = protocol {
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, protocol.clone());
+        }
+
+        tokio::spawn(async move {
+            let upgraded = match on_upgrade.await {
+                Ok(upgraded) => upgraded,
+                Err(err) => {
+                    log::error!("Couldn't upgrade the connection to a WebSocket: {}", err);
+                    return;
+                }
+            };
+
+            let inner = WebSocketStream::from_raw_socket(upgraded, Role::Server, config).await;
+            let protocol = protocol.and_then(|protocol| protocol.to_str().ok().map(ToOwned::to_owned));
+
+            callback(WebSocket { inner, protocol }).await;
+        });
+
+        res
+    }
+}
+
+impl Debug for WebSocketUpgrade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketUpgrade")
+            .field("requested_protocols", &self.requested_protocols)
+            .field("protocol", &self.protocol)
+            .finish()
+    }
+}
+
+impl<ReqBody: Send + 'static> FromRequest<ReqBody> for WebSocketUpgrade {
+    fn from_request(mut req: Request<ReqBody>) -> ExtractFuture<Self> {
+        Box::pin(std::future::ready(WebSocketUpgrade::new(&mut req)))
+    }
+}
+
+/// An upgraded WebSocket connection, it receives and sends the messages.
+///
+/// It's also a `Stream` of the received messages and a `Sink` of the sent messages, so it can be split into two
+/// halves with the `futures` crate.
+pub struct WebSocket {
+    inner: WebSocketStream<Upgraded>,
+    protocol: Option<String>,
+}
+
+impl WebSocket {
+    /// Receives the next message, or `None` after the connection is closed.
+    ///
+    /// The pings are answered automatically, but they're returned too.
+    pub async fn recv(&mut self) -> Option<Result<Message, WebSocketError>> {
+        self.inner.next().await
+    }
+
+    /// Sends a message.
+    pub async fn send(&mut self, msg: Message) -> Result<(), WebSocketError> {
+        self.inner.send(msg).await
+    }
+
+    /// Closes the connection with an optional close frame.
+    pub async fn close(mut self, frame: Option<CloseFrame>) -> Result<(), WebSocketError> {
+        self.inner.close(frame).await
+    }
+
+    /// Returns the subprotocol which was selected with
+    /// [`WebSocketUpgrade::protocols`](./struct.WebSocketUpgrade.html#method.protocols).
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+}
+
+impl Debug for WebSocket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket").field("protocol", &self.protocol).finish()
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Message, WebSocketError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for WebSocket {
+    type Error = WebSocketError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+// Checks if a comma separated header contains the token, ignoring the case.
+fn has_token(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .any(|val| val.trim().eq_ignore_ascii_case(token))
+}
+
+fn accept_key(key: &[u8]) -> HeaderValue {
+    let mut sha1 = Sha1::new();
+    sha1.update(key);
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+
+    HeaderValue::from_str(&BASE64.encode(sha1.finalize())).expect("The base64 encoding is a valid header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_key() {
+        // The example of RFC 6455.
+        assert_eq!(accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
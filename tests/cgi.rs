@@ -0,0 +1,268 @@
+#![cfg(feature = "cgi")]
+
+use hyper::{Body, Request, Response};
+use routerify::cgi::{self, CgiHandler};
+use routerify::extract::BodyLimit;
+use routerify::prelude::*;
+use routerify::{RouteError, Router};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn handler() -> CgiHandler<Body, RouteError> {
+    let router = Router::builder()
+        .any(|req: Request<Body>| async move {
+            let text = format!(
+                "{} {} from {} ",
+                req.method(),
+                req.uri(),
+                req.remote_addr()
+                    .as_tcp()
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_default()
+            );
+            let content_type = req
+                .headers()
+                .get("content-type")
+                .map(|val| val.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+
+            Ok(Response::builder()
+                .status(201)
+                .header("x-content-type", content_type)
+                .body(Body::from([text.as_bytes(), &body].concat()))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+
+    CgiHandler::new(router).unwrap()
+}
+
+fn vars(method: &str, path: &str, body: &str) -> Vec<(String, String)> {
+    vec![
+        ("REQUEST_METHOD", method),
+        ("PATH_INFO", path),
+        ("QUERY_STRING", "a=1&b=%20"),
+        ("SERVER_PROTOCOL", "HTTP/1.1"),
+        ("REMOTE_ADDR", "192.0.2.1"),
+        ("REMOTE_PORT", "4711"),
+        ("CONTENT_TYPE", "text/plain"),
+        ("HTTP_X_REQUEST_ID", "42"),
+        ("GATEWAY_INTERFACE", "CGI/1.1"),
+    ]
+    .into_iter()
+    .map(|(key, val)| (key.to_owned(), val.to_owned()))
+    .chain(std::iter::once(("CONTENT_LENGTH".to_owned(), body.len().to_string())))
+    .collect()
+}
+
+#[tokio::test]
+async fn can_convert_variables_into_requests() {
+    let req = cgi::into_request(vars("POST", "/users/a b", ""), Body::empty()).unwrap();
+    assert_eq!(req.method(), "POST");
+    assert_eq!(req.uri(), "/users/a%20b?a=1&b=%20");
+    assert_eq!(req.headers()["x-request-id"], "42");
+    assert_eq!(req.headers()["content-type"], "text/plain");
+    assert_eq!(req.headers()["content-length"], "0");
+    assert!(!req.headers().contains_key("gateway-interface"));
+
+    assert!(cgi::into_request(Vec::<(String, String)>::new(), Body::empty()).is_err());
+}
+
+#[tokio::test]
+async fn can_handle_cgi_requests() {
+    let mut stdout = Vec::new();
+    handler()
+        .handle_cgi(vars("POST", "/users", "hello"), &b"hello, and more"[..], &mut stdout)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "Status: 201 Created\r\nx-content-type: text/plain\r\n\r\n\
+         POST /users?a=1&b=%20 from 192.0.2.1:4711 hello"
+    );
+
+    let mut stdout = Vec::new();
+    handler()
+        .body_limit(BodyLimit(4))
+        .handle_cgi(vars("POST", "/users", "hello"), &b"hello"[..], &mut stdout)
+        .await
+        .unwrap();
+
+    assert!(String::from_utf8(stdout)
+        .unwrap()
+        .starts_with("Status: 413 Payload Too Large\r\n"));
+
+    // The request method is missing.
+    let vars = vars("GET", "/users", "")
+        .into_iter()
+        .filter(|(name, _)| name != "REQUEST_METHOD");
+    let mut stdout = Vec::new();
+    handler().handle_cgi(vars, &b""[..], &mut stdout).await.unwrap();
+
+    assert!(String::from_utf8(stdout)
+        .unwrap()
+        .starts_with("Status: 400 Bad Request\r\n"));
+}
+
+fn record(kind: u8, request_id: u16, content: &[u8]) -> Vec<u8> {
+    let mut record = vec![1, kind, 0, 0, 0, 0, 0, 0];
+    record[2..4].copy_from_slice(&request_id.to_be_bytes());
+    record[4..6].copy_from_slice(&(content.len() as u16).to_be_bytes());
+    record.extend_from_slice(content);
+    record
+}
+
+fn fastcgi_request(request_id: u16, keep_conn: bool, path: &str, body: &str) -> Vec<u8> {
+    fastcgi_request_with_vars(request_id, keep_conn, vars("PUT", path, body), body)
+}
+
+fn fastcgi_request_with_vars(request_id: u16, keep_conn: bool, vars: Vec<(String, String)>, body: &str) -> Vec<u8> {
+    let mut params = Vec::new();
+    for (name, val) in vars {
+        params.push(name.len() as u8);
+        params.push(val.len() as u8);
+        params.extend_from_slice(name.as_bytes());
+        params.extend_from_slice(val.as_bytes());
+    }
+
+    [
+        record(1, request_id, &[0, 1, keep_conn as u8, 0, 0, 0, 0, 0]),
+        record(4, request_id, &params),
+        record(4, request_id, &[]),
+        record(5, request_id, body.as_bytes()),
+        record(5, request_id, &[]),
+    ]
+    .concat()
+}
+
+// Collects the stdout of the requests until their end request records.
+fn parse_records(mut buf: &[u8]) -> Vec<(u8, u16, Vec<u8>)> {
+    let mut records = Vec::new();
+    while !buf.is_empty() {
+        let kind = buf[1];
+        let request_id = u16::from_be_bytes([buf[2], buf[3]]);
+        let content_len = u16::from_be_bytes([buf[4], buf[5]]) as usize;
+        let padding_len = buf[6] as usize;
+        records.push((kind, request_id, buf[8..8 + content_len].to_vec()));
+        buf = &buf[8 + content_len + padding_len..];
+    }
+    records
+}
+
+fn stdout(records: &[(u8, u16, Vec<u8>)], request_id: u16) -> String {
+    let stdout = records
+        .iter()
+        .filter(|(kind, id, _)| *kind == 6 && *id == request_id)
+        .flat_map(|(_, _, content)| content.clone())
+        .collect::<Vec<u8>>();
+    String::from_utf8(stdout).unwrap()
+}
+
+#[tokio::test]
+async fn can_serve_fastcgi_connections() {
+    let (mut client, server) = tokio::io::duplex(1024);
+
+    let serve = async {
+        handler().serve_fastcgi_connection(server).await.unwrap();
+    };
+
+    let requests = async {
+        client
+            .write_all(&fastcgi_request(1, true, "/first", "one"))
+            .await
+            .unwrap();
+        client
+            .write_all(&fastcgi_request(2, false, "/second", "two"))
+            .await
+            .unwrap();
+
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await.unwrap();
+        buf
+    };
+
+    let (_, buf) = tokio::join!(serve, requests);
+    let records = parse_records(&buf);
+
+    assert_eq!(
+        stdout(&records, 1),
+        "Status: 201 Created\r\nx-content-type: text/plain\r\n\r\n\
+         PUT /first?a=1&b=%20 from 192.0.2.1:4711 one"
+    );
+    assert!(stdout(&records, 2).ends_with("PUT /second?a=1&b=%20 from 192.0.2.1:4711 two"));
+
+    let end_requests = records
+        .iter()
+        .filter(|(kind, _, _)| *kind == 3)
+        .map(|(_, id, content)| (*id, content[4]))
+        .collect::<Vec<(u16, u8)>>();
+    assert_eq!(end_requests, vec![(1, 0), (2, 0)]);
+}
+
+#[tokio::test]
+async fn can_reject_invalid_fastcgi_requests() {
+    let (mut client, server) = tokio::io::duplex(1024);
+
+    let serve = async {
+        handler()
+            .body_limit(BodyLimit(4))
+            .serve_fastcgi_connection(server)
+            .await
+            .unwrap();
+    };
+
+    let requests = async {
+        // The body is larger than the limit.
+        client
+            .write_all(&fastcgi_request(1, true, "/first", "hello"))
+            .await
+            .unwrap();
+        // The request method is missing.
+        let vars = vars("PUT", "/second", "")
+            .into_iter()
+            .filter(|(name, _)| name != "REQUEST_METHOD")
+            .collect();
+        client
+            .write_all(&fastcgi_request_with_vars(2, true, vars, ""))
+            .await
+            .unwrap();
+        // The params are larger than the limit.
+        client
+            .write_all(&record(1, 3, &[0, 1, 1, 0, 0, 0, 0, 0]))
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            client.write_all(&record(4, 3, &[0; 60_000])).await.unwrap();
+        }
+        client
+            .write_all(&[record(4, 3, &[]), record(5, 3, &[])].concat())
+            .await
+            .unwrap();
+        // The connection is still served.
+        client
+            .write_all(&fastcgi_request(4, false, "/fourth", "ok"))
+            .await
+            .unwrap();
+
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await.unwrap();
+        buf
+    };
+
+    let (_, buf) = tokio::join!(serve, requests);
+    let records = parse_records(&buf);
+
+    assert!(stdout(&records, 1).starts_with("Status: 413 Payload Too Large\r\n"));
+    assert!(stdout(&records, 2).starts_with("Status: 400 Bad Request\r\n"));
+    assert!(stdout(&records, 3).starts_with("Status: 431 Request Header Fields Too Large\r\n"));
+    assert!(stdout(&records, 4).ends_with("PUT /fourth?a=1&b=%20 from 192.0.2.1:4711 ok"));
+
+    let end_requests = records
+        .iter()
+        .filter(|(kind, _, _)| *kind == 3)
+        .map(|(_, id, content)| (*id, content[4]))
+        .collect::<Vec<(u16, u8)>>();
+    assert_eq!(end_requests, vec![(1, 0), (2, 0), (3, 0), (4, 0)]);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "compression")]
+
+use self::support::{into_text, serve};
+use hyper::body::{Bytes, HttpBody};
+use hyper::{Body, Client, Response, StatusCode};
+use routerify::compression::Compression;
+use routerify::{RouteError, Router};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+mod support;
+
+fn text() -> String {
+    "Hello, World! ".repeat(200)
+}
+
+fn router(notify: Arc<Notify>) -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/text", || async move { Ok(Response::new(Body::from(text()))) })
+        .get("/small", || async move { Ok(Response::new(Body::from("Hello"))) })
+        .get("/image", || async move {
+            Ok(Response::builder()
+                .header("content-type", "image/png")
+                .body(Body::from(text()))
+                .unwrap())
+        })
+        .get("/stream", move || {
+            let notify = notify.clone();
+            async move {
+                let (mut sender, body) = Body::channel();
+                tokio::spawn(async move {
+                    sender.send_data(Bytes::from_static(b"first chunk")).await.unwrap();
+                    notify.notified().await;
+                    sender.send_data(Bytes::from_static(b", second chunk")).await.unwrap();
+                });
+                Ok(Response::new(body))
+            }
+        })
+        .middleware(Compression::new().middleware())
+        .build()
+        .unwrap()
+}
+
+async fn request(serve: &support::Serve, path: &str, accept_encoding: Option<&str>) -> Response<Body> {
+    let mut req = serve.new_request("GET", path);
+    if let Some(accept_encoding) = accept_encoding {
+        req = req.header("accept-encoding", accept_encoding);
+    }
+    Client::new().request(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+async fn into_bytes(body: Body) -> Vec<u8> {
+    hyper::body::to_bytes(body).await.unwrap().to_vec()
+}
+
+#[tokio::test]
+async fn can_compress_responses() {
+    let serve = serve(router(Arc::new(Notify::new()))).await;
+
+    let resp = request(&serve, "/text", Some("gzip, deflate, br")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-encoding"], "br");
+    assert_eq!(resp.headers()["vary"], "accept-encoding");
+    assert!(!resp.headers().contains_key("content-length"));
+    let mut decoded = String::new();
+    brotli::Decompressor::new(&into_bytes(resp.into_body()).await[..], 4096)
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text());
+
+    let resp = request(&serve, "/text", Some("deflate;q=0.5, gzip")).await;
+    assert_eq!(resp.headers()["content-encoding"], "gzip");
+    let encoded = into_bytes(resp.into_body()).await;
+    assert!(encoded.len() < text().len());
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&encoded[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text());
+
+    let resp = request(&serve, "/text", Some("deflate")).await;
+    assert_eq!(resp.headers()["content-encoding"], "deflate");
+    let mut decoded = String::new();
+    flate2::read::ZlibDecoder::new(&into_bytes(resp.into_body()).await[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text());
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn skips_the_responses_which_should_not_be_compressed() {
+    let serve = serve(router(Arc::new(Notify::new()))).await;
+
+    // The response could be compressed, but the client doesn't accept any encoding.
+    let resp = request(&serve, "/text", None).await;
+    assert!(!resp.headers().contains_key("content-encoding"));
+    assert_eq!(resp.headers()["vary"], "accept-encoding");
+    assert_eq!(into_text(resp.into_body()).await, text());
+
+    let resp = request(&serve, "/text", Some("gzip;q=0, identity")).await;
+    assert!(!resp.headers().contains_key("content-encoding"));
+
+    for path in ["/small", "/image"] {
+        let resp = request(&serve, path, Some("gzip")).await;
+        assert!(!resp.headers().contains_key("content-encoding"), "{}", path);
+        assert!(!resp.headers().contains_key("vary"), "{}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_compress_streaming_bodies_incrementally() {
+    let notify = Arc::new(Notify::new());
+    let serve = serve(router(notify.clone())).await;
+
+    let resp = request(&serve, "/stream", Some("gzip")).await;
+    assert_eq!(resp.headers()["content-encoding"], "gzip");
+
+    let mut body = resp.into_body();
+    let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+
+    // The first chunk arrives while the second one is held back.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while decoder.get_ref().as_slice() != b"first chunk" {
+            let data = body.data().await.unwrap().unwrap();
+            decoder.write_all(&data).unwrap();
+            decoder.flush().unwrap();
+        }
+    })
+    .await
+    .expect("The first chunk wasn't flushed");
+
+    notify.notify_one();
+    while let Some(data) = body.data().await {
+        decoder.write_all(&data.unwrap()).unwrap();
+    }
+    assert_eq!(decoder.finish().unwrap(), b"first chunk, second chunk");
+
+    serve.shutdown();
+}
@@ -0,0 +1,115 @@
+use hyper::server::conn::Http;
+use hyper::service::Service;
+use hyper::{Body, Request, Response};
+use routerify::prelude::*;
+use routerify::{Middleware, PeerAddr, RequestInfo, RouteError, Router, RouterService};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/", |req: Request<Body>| async move {
+            let text = match req.remote_addr() {
+                PeerAddr::Tcp(addr) => format!("tcp {}", addr.ip()),
+                PeerAddr::Unix(_) => "unix".to_string(),
+                PeerAddr::Unknown => "unknown".to_string(),
+            };
+            Ok(Response::new(Body::from(text)))
+        })
+        .middleware(Middleware::post_with_info(
+            |mut res, req_info: RequestInfo| async move {
+                let local_addr = req_info.connection_info().local_addr().to_string();
+                res.headers_mut().insert("x-local-addr", local_addr.parse().unwrap());
+                Ok(res)
+            },
+        ))
+        .build()
+        .unwrap()
+}
+
+async fn request<IO: AsyncReadExt + AsyncWriteExt + Unpin>(mut io: IO) -> String {
+    io.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    io.read_to_string(&mut resp).await.unwrap();
+    resp
+}
+
+#[tokio::test]
+async fn can_serve_over_duplex_stream() {
+    let mut router_service = RouterService::new(router()).unwrap();
+    let (client, server) = tokio::io::duplex(4096);
+    let service = router_service.call(&server).await.unwrap();
+    tokio::spawn(Http::new().serve_connection(server, service));
+
+    let resp = request(client).await;
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.contains("x-local-addr: unknown\r\n"));
+    assert!(resp.ends_with("\r\n\r\nunknown"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn can_serve_over_unix_socket() {
+    use hyper::server::accept;
+    use hyper::Server;
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = std::env::temp_dir().join(format!("routerify-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let incoming = accept::poll_fn(move |cx| listener.poll_accept(cx).map(|res| Some(res.map(|(stream, _)| stream))));
+    tokio::spawn(Server::builder(incoming).serve(RouterService::new(router()).unwrap()));
+
+    let resp = request(UnixStream::connect(&path).await.unwrap()).await;
+    std::fs::remove_file(&path).unwrap();
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.ends_with("\r\n\r\nunix"));
+}
+
+#[tokio::test]
+async fn can_serve_over_tcp_stream() {
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut router_service = RouterService::new(router()).unwrap();
+
+    let (_, resp) = tokio::join!(
+        async {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = router_service.call(&stream).await.unwrap();
+            Http::new().serve_connection(stream, service).await.unwrap();
+        },
+        async { request(TcpStream::connect(addr).await.unwrap()).await }
+    );
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.contains(&format!("x-local-addr: {}\r\n", addr)));
+    assert!(resp.ends_with("\r\n\r\ntcp 127.0.0.1"));
+}
+
+#[tokio::test]
+async fn can_serve_behind_proxy_protocol() {
+    use routerify::proxy_protocol;
+
+    let mut router_service = RouterService::new(router()).unwrap();
+    let (mut client, server) = tokio::io::duplex(4096);
+
+    let (_, resp) = tokio::join!(
+        async {
+            let stream = proxy_protocol::accept(server).await.unwrap();
+            let service = router_service.call(&stream).await.unwrap();
+            Http::new().serve_connection(stream, service).await.unwrap();
+        },
+        async {
+            client
+                .write_all(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n")
+                .await
+                .unwrap();
+            request(client).await
+        }
+    );
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.contains("x-local-addr: 198.51.100.1:443\r\n"));
+    assert!(resp.ends_with("\r\n\r\ntcp 192.0.2.1"));
+}
@@ -0,0 +1,166 @@
+use self::support::{into_text, serve};
+use hyper::{Body, Client, HeaderMap, Method, Request, Response, StatusCode};
+use routerify::extract::{handler, Context, Data, Path, Rejection};
+use routerify::prelude::*;
+use routerify::{Middleware, PeerAddr, RouteError, RouteParams, Router};
+
+mod support;
+
+#[derive(Clone)]
+struct Greeting(&'static str);
+
+async fn get_user(
+    Path(id): Path<u64>,
+    Data(greeting): Data<Greeting>,
+    Context(request_id): Context<u32>,
+    addr: PeerAddr,
+    headers: HeaderMap,
+) -> Result<Response<Body>, RouteError> {
+    let text = format!(
+        "{} user {} with request {} from {} as {}",
+        greeting.0,
+        id,
+        request_id,
+        addr.as_tcp().unwrap().ip(),
+        headers["user-agent"].to_str().unwrap()
+    );
+    Ok(Response::new(Body::from(text)))
+}
+
+async fn update_user(method: Method, params: RouteParams, body: String) -> Result<Response<Body>, RouteError> {
+    let text = format!(
+        "{} {}/{}: {}",
+        method,
+        params.get("org").unwrap(),
+        params.get("user").unwrap(),
+        body
+    );
+    Ok(Response::new(Body::from(text)))
+}
+
+async fn get_status(count: Option<Data<usize>>) -> Result<Response<Body>, RouteError> {
+    Ok(Response::new(Body::from(format!("{:?}", count.map(|count| count.0)))))
+}
+
+#[tokio::test]
+async fn can_extract_handler_arguments() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .data(Greeting("Hello"))
+        .middleware(Middleware::pre(|req| async move {
+            req.set_context(42u32);
+            Ok(req)
+        }))
+        .get("/users/:id", handler(get_user))
+        .put("/orgs/:org/users/:user", handler(update_user))
+        .get("/status", handler(get_status))
+        .get(
+            "/ping",
+            handler(|| async move { Ok(Response::new(Body::from("pong"))) }),
+        )
+        .build()
+        .unwrap();
+
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    let req = serve
+        .new_request("GET", "/users/7")
+        .header("user-agent", "test")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Hello user 7 with request 42 from 127.0.0.1 as test"
+    );
+
+    let req = serve
+        .new_request("PUT", "/orgs/acme/users/alice")
+        .body(Body::from("admin"))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "PUT acme/alice: admin");
+
+    let req = serve.new_request("GET", "/status").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "None");
+
+    let req = serve.new_request("GET", "/ping").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "pong");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_with_the_status_of_rejections() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .middleware(Middleware::pre(|req| async move {
+            req.set_context(42u32);
+            Ok(req)
+        }))
+        .get("/users/:id", handler(get_user))
+        .build()
+        .unwrap();
+
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    // The route parameter isn't a number.
+    let req = serve.new_request("GET", "/users/alice").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Invalid route parameter `id`: invalid digit found in string"
+    );
+
+    // The `Greeting` data isn't shared with the router.
+    let req = serve.new_request("GET", "/users/7").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "No data of type `extract::Greeting` is shared with the router"
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_handle_rejections_in_error_handler() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .get(
+            "/users/:id",
+            handler(|Path(id): Path<u64>| async move { Ok(Response::new(Body::from(id.to_string()))) }),
+        )
+        .err_handler(|err: RouteError| async move {
+            let rejection = err.downcast_ref::<Rejection>().unwrap();
+            Response::builder()
+                .status(rejection.status())
+                .body(Body::from(rejection.message().to_owned()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/users/alice", serve.addr()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Invalid route parameter `id`: invalid digit found in string"
+    );
+
+    serve.shutdown();
+}
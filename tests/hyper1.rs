@@ -0,0 +1,72 @@
+#![cfg(feature = "hyper1")]
+
+use hyper::{Body, Request, Response};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use routerify::prelude::*;
+use routerify::{RequestServiceBuilder, RouteError, Router};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn can_serve_hyper1_connection() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .post("/users/:name", |req: Request<Body>| async move {
+            let name = req.param("name").unwrap().to_string();
+            let remote_addr = req.remote_addr();
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+            let text = format!("{} {} from {}", String::from_utf8_lossy(&body), name, remote_addr);
+            Ok(Response::builder().header("x-name", name).body(Body::from(text))?)
+        })
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+    let remote_addr: SocketAddr = "10.0.0.1:4321".parse().unwrap();
+
+    let (mut client, server) = tokio::io::duplex(4096);
+    let service = builder.build_hyper1(remote_addr);
+    let serve = tokio::spawn(async move {
+        Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(server), service)
+            .await
+            .unwrap();
+    });
+
+    client
+        .write_all(
+            b"POST /users/alice HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        )
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    client.read_to_string(&mut resp).await.unwrap();
+    serve.await.unwrap();
+
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.contains("x-name: alice\r\n"));
+    assert!(resp.ends_with("\r\n\r\nhello alice from 10.0.0.1:4321"));
+}
+
+#[tokio::test]
+async fn can_serve_default_404_over_hyper1() {
+    let router: Router<Body, RouteError> = Router::builder().build().unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+
+    let (mut client, server) = tokio::io::duplex(4096);
+    let service = builder.build_hyper1("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    tokio::spawn(async move {
+        let _ = Builder::new(TokioExecutor::new())
+            .serve_connection(TokioIo::new(server), service)
+            .await;
+    });
+
+    client
+        .write_all(b"GET /unknown HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    client.read_to_string(&mut resp).await.unwrap();
+
+    assert!(resp.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    assert!(resp.ends_with("\r\n\r\nNot Found"));
+}
@@ -1,5 +1,6 @@
 use self::support::{into_text, serve};
 use hyper::{Body, Client, Request, Response, StatusCode};
+use routerify::extract::Rejection;
 use routerify::prelude::RequestExt;
 use routerify::{Middleware, RequestInfo, RouteError, Router};
 use std::io;
@@ -11,7 +12,7 @@ mod support;
 async fn can_perform_simple_get_request() {
     const RESPONSE_TEXT: &str = "Hello world";
     let router: Router<Body, routerify::Error> = Router::builder()
-        .get("/", |_| async move { Ok(Response::new(RESPONSE_TEXT.into())) })
+        .get("/", || async move { Ok(Response::new(RESPONSE_TEXT.into())) })
         .err_handler(|_: RouteError| async move { todo!() })
         .build()
         .unwrap();
@@ -36,7 +37,7 @@ async fn can_perform_simple_get_request_boxed_error() {
     const RESPONSE_TEXT: &str = "Hello world";
     type BoxedError = Box<dyn std::error::Error + Sync + Send + 'static>;
     let router: Router<Body, BoxedError> = Router::builder()
-        .get("/", |_| async move { Ok(Response::new(RESPONSE_TEXT.into())) })
+        .get("/", || async move { Ok(Response::new(RESPONSE_TEXT.into())) })
         .err_handler(|_: RouteError| async move { todo!() })
         .build()
         .unwrap();
@@ -163,7 +164,7 @@ async fn can_propagate_request_context() {
 
         // Trigger this error in order to invoke
         // the error handler.
-        Err(io::Error::new(io::ErrorKind::AddrInUse, "bogus error"))
+        Err::<Response<Body>, _>(io::Error::new(io::ErrorKind::AddrInUse, "bogus error"))
     };
 
     let error_handler = |_err, req_info: RequestInfo| async move {
@@ -227,7 +228,7 @@ async fn can_propagate_request_context() {
 async fn can_extract_path_params() {
     const RESPONSE_TEXT: &str = "Hello world";
     let router: Router<Body, routerify::Error> = Router::builder()
-        .get("/api/:first/plus/:second", |req| async move {
+        .get("/api/:first/plus/:second", |req: Request<Body>| async move {
             let first = req.param("first").unwrap();
             let second = req.param("second").unwrap();
             assert_eq!(first, "40");
@@ -257,11 +258,118 @@ async fn can_extract_path_params() {
     serve.shutdown();
 }
 
+#[tokio::test]
+async fn can_parse_path_params() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .get("/api/:first/plus/:second", |req: Request<Body>| async move {
+            let first = req.params().parse::<u32>("first").map_err(Rejection::from)?;
+            let second = req.params().parse::<u32>("second").map_err(Rejection::from)?;
+            Ok((first + second).to_string())
+        })
+        .get("/api/:first/minus/:second", |req: Request<Body>| async move {
+            let third = req.params().parse::<u32>("third").map_err(Rejection::from)?;
+            Ok(third.to_string())
+        })
+        .get("/api/:first/times/:second", |req: Request<Body>| async move {
+            let first = req.params().parse::<u32>("first")?;
+            let second = req.params().parse::<u32>("second")?;
+            Ok((first * second).to_string())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    let req = serve.new_request("GET", "/api/40/plus/2").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "42");
+
+    let req = serve
+        .new_request("GET", "/api/40/plus/two")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = serve.new_request("GET", "/api/40/minus/2").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // The `ParamError` isn't converted into a `Rejection`, the default error handler still responds with its status.
+    let req = serve
+        .new_request("GET", "/api/6/times/seven")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Invalid route parameter `second`: invalid digit found in string"
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_access_query_params() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .middleware(Middleware::post_with_info(
+            |mut res, req_info: RequestInfo| async move {
+                let sort = req_info.query("sort").unwrap_or("none").parse().unwrap();
+                res.headers_mut().insert("x-sort", sort);
+                Ok(res)
+            },
+        ))
+        .get("/books", |req: Request<Body>| async move {
+            let page = req
+                .query_params()
+                .parse::<u32>("page")
+                .map_err(Rejection::from)?
+                .unwrap_or(1);
+            Ok(format!("{} {:?}", page, req.query_all("tag")))
+        })
+        .get("/authors", |req: Request<Body>| async move {
+            let page = req.query_params().parse::<u32>("page")?.unwrap_or(1);
+            Ok(page.to_string())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    let req = serve
+        .new_request("GET", "/books?tag=sci%20fi&page=2&tag=a+b&sort=title")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.headers()["x-sort"], "title");
+    assert_eq!(into_text(resp.into_body()).await, r#"2 ["sci fi", "a b"]"#);
+
+    let req = serve.new_request("GET", "/books").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.headers()["x-sort"], "none");
+    assert_eq!(into_text(resp.into_body()).await, "1 []");
+
+    let req = serve.new_request("GET", "/books?page=two").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // The `QueryError` isn't converted into a `Rejection`, the default error handler still responds with its status.
+    let req = serve
+        .new_request("GET", "/authors?page=two")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    serve.shutdown();
+}
+
 #[tokio::test]
 async fn can_extract_extension_path_params_1() {
     const RESPONSE_TEXT: &str = "Hello world";
     let router: Router<Body, routerify::Error> = Router::builder()
-        .get("/api/:id.json", |req| async move {
+        .get("/api/:id.json", |req: Request<Body>| async move {
             let id = req.param("id").unwrap();
             assert_eq!(id, "40");
             let (parts, _) = req.into_parts();
@@ -291,7 +399,7 @@ async fn can_extract_extension_path_params_1() {
 async fn can_extract_extension_path_params_2() {
     const RESPONSE_TEXT: &str = "Hello world";
     let router: Router<Body, routerify::Error> = Router::builder()
-        .get("/api/:fileName", |req| async move {
+        .get("/api/:fileName", |req: Request<Body>| async move {
             let file_name = req.param("fileName").unwrap();
             assert_eq!(file_name, "data.json");
             let (parts, _) = req.into_parts();
@@ -322,14 +430,14 @@ async fn do_not_execute_scoped_middleware_for_unscoped_path() {
     let api_router: Router<Body, routerify::Error> = Router::builder()
         .middleware(Middleware::pre(|_| async { panic!("should not be executed") }))
         .middleware(Middleware::post(|_| async { panic!("should not be executed") }))
-        .get("/api/todo", |_| async { Ok(Response::new("".into())) })
+        .get("/api/todo", || async { Ok(Response::new("".into())) })
         .build()
         .unwrap();
 
     let router: Router<Body, routerify::Error> = Router::builder()
-        .get("/", |_| async { Ok(Response::new("".into())) })
+        .get("/", || async { Ok(Response::new("".into())) })
         .scope("/api", api_router)
-        .get("/api/login", |_| async { Ok(Response::new("".into())) })
+        .get("/api/login", || async { Ok(Response::new("".into())) })
         .build()
         .unwrap();
 
@@ -370,16 +478,16 @@ async fn execute_scoped_middleware_when_no_unscoped_match() {
             post.0.store(true, SeqCst);
             Ok(req)
         }))
-        .get("/api/todo", |_| async { Ok(Response::new("".into())) })
+        .get("/api/todo", || async { Ok(Response::new("".into())) })
         .build()
         .unwrap();
 
     let router: Router<Body, routerify::Error> = Router::builder()
         .data(executed_pre.clone())
         .data(executed_post.clone())
-        .get("/", |_| async { Ok(Response::new("".into())) })
+        .get("/", || async { Ok(Response::new("".into())) })
         .scope("/api", api_router)
-        .get("/api/login", |_| async { Ok(Response::new("".into())) })
+        .get("/api/login", || async { Ok(Response::new("".into())) })
         .build()
         .unwrap();
 
@@ -418,7 +526,9 @@ async fn can_handle_custom_errors() {
 
     const RESPONSE_TEXT: &str = "Something went wrong!";
     let router: Router<Body, ApiError> = Router::builder()
-        .get("/", |_| async move { Err(ApiError::Generic(RESPONSE_TEXT.into())) })
+        .get("/", || async move {
+            Err::<Response<Body>, _>(ApiError::Generic(RESPONSE_TEXT.into()))
+        })
         .err_handler(|err: RouteError| async move {
             let api_err = err.downcast::<ApiError>().unwrap();
             match api_err.as_ref() {
@@ -479,7 +589,9 @@ async fn can_handle_pre_middleware_errors() {
             let _state = req_info.data::<State>().expect("No state");
             Ok(resp)
         }))
-        .get("/", |_| async { panic!("should not be executed") })
+        .get("/", || async {
+            panic!("should not be executed") as Result<Response<Body>, _>
+        })
         .build()
         .unwrap();
 
@@ -496,3 +608,148 @@ async fn can_handle_pre_middleware_errors() {
         .unwrap();
     serve.shutdown();
 }
+
+#[tokio::test]
+async fn can_mount_services() {
+    use hyper::service::service_fn;
+
+    struct Tenant(&'static str);
+
+    let echo = service_fn(|req: Request<Body>| async move {
+        let tenant = req.data::<Tenant>().unwrap().0;
+        let seen = req.context::<String>().unwrap();
+        Ok::<_, routerify::Error>(Response::new(Body::from(format!("{} {} {}", tenant, seen, req.uri()))))
+    });
+
+    let admin: Router<Body, routerify::Error> = Router::builder()
+        .data(Tenant("acme"))
+        .middleware(Middleware::pre(|req| async move {
+            req.set_context("pre".to_string());
+            Ok(req)
+        }))
+        .mount("/full", echo)
+        .mount_with_stripped_prefix("/stripped", echo)
+        .build()
+        .unwrap();
+    let router: Router<Body, routerify::Error> = Router::builder().scope("/admin", admin).build().unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/admin/full/users?page=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "acme pre /admin/full/users?page=2");
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "/admin/stripped/users?page=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "acme pre /users?page=2");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/admin/stripped").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "acme pre /");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_resolve_client_behind_trusted_proxy() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .trusted_proxies(vec!["127.0.0.0/8"])
+        .get("/", |req: Request<Body>| async move {
+            let client = req.client_info();
+            let text = format!(
+                "{} {} {} {}",
+                req.remote_addr().as_tcp().unwrap().ip(),
+                client.addr(),
+                client.scheme().unwrap_or("-"),
+                client.host().unwrap_or("-")
+            );
+            Ok(Response::new(Body::from(text)))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/")
+                .header("forwarded", "for=\"203.0.113.7:4711\";proto=https;host=example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "127.0.0.1 203.0.113.7:4711 https example.com");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert!(resp.starts_with("127.0.0.1 127.0.0.1:"));
+    assert!(resp.ends_with(" - -"));
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_with_into_response_types() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/text", || async move { Ok("Hello") })
+        .get("/created", || async move {
+            Ok((StatusCode::CREATED, [("location", "/users/7")], format!("User {}", 7)))
+        })
+        .delete("/users/:userId", || async move { Ok(StatusCode::NO_CONTENT) })
+        .get("/result", || async move {
+            let res: Result<&'static str, StatusCode> = Err(StatusCode::CONFLICT);
+            Ok(res)
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    let resp = client
+        .request(serve.new_request("GET", "/text").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    assert_eq!(into_text(resp.into_body()).await, "Hello");
+
+    let resp = client
+        .request(serve.new_request("GET", "/created").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.headers()["location"], "/users/7");
+    assert_eq!(into_text(resp.into_body()).await, "User 7");
+
+    let resp = client
+        .request(serve.new_request("DELETE", "/users/7").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = client
+        .request(serve.new_request("GET", "/result").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    serve.shutdown();
+}
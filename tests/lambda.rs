@@ -0,0 +1,144 @@
+#![cfg(feature = "lambda")]
+
+use hyper::{Body, Request, Response};
+use routerify::lambda::{self, EventKind, LambdaHandler};
+use routerify::prelude::*;
+use routerify::{RouteError, Router};
+use serde_json::{json, Value};
+
+fn event(name: &str) -> Value {
+    let json = match name {
+        "apigw_v1" => include_str!("fixtures/lambda/apigw_v1.json"),
+        "apigw_v2" => include_str!("fixtures/lambda/apigw_v2.json"),
+        "alb" => include_str!("fixtures/lambda/alb.json"),
+        _ => unreachable!(),
+    };
+    serde_json::from_str(json).unwrap()
+}
+
+fn handler() -> LambdaHandler<Body, RouteError> {
+    let router = Router::builder()
+        .any(|req: Request<Body>| async move {
+            let text = format!(
+                "{} {} from {}",
+                req.method(),
+                req.uri(),
+                req.remote_addr()
+                    .as_tcp()
+                    .map(|addr| addr.ip().to_string())
+                    .unwrap_or_default()
+            );
+            let cookie = req
+                .headers()
+                .get("cookie")
+                .map(|val| val.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+
+            Ok(Response::builder()
+                .header("x-body", std::str::from_utf8(&body).unwrap())
+                .header("x-cookie", cookie)
+                .header("set-cookie", "a=1")
+                .header("set-cookie", "b=2")
+                .body(Body::from(text))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+
+    LambdaHandler::new(router).unwrap()
+}
+
+#[tokio::test]
+async fn can_convert_events_into_requests() {
+    let (req, kind) = lambda::into_request(event("apigw_v1")).unwrap();
+    assert_eq!(kind, EventKind::ApiGatewayV1);
+    assert_eq!(req.method(), "POST");
+    assert_eq!(req.uri().path(), "/users/alice");
+    let query = req.uri().query().unwrap();
+    assert!(query.contains("tag=a&tag=b"));
+    assert!(query.contains("q=a%20b%26c"));
+    assert_eq!(
+        req.headers().get_all("x-custom").iter().collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+    assert_eq!(
+        hyper::body::to_bytes(req.into_body()).await.unwrap(),
+        "{\"name\":\"alice\"}"
+    );
+
+    let (req, kind) = lambda::into_request(event("alb")).unwrap();
+    assert_eq!(
+        kind,
+        EventKind::Alb {
+            multi_value_headers: false
+        }
+    );
+    assert_eq!(req.uri(), "/users/carol?q=a%20b");
+
+    // The REST API sends the decoded path, the other events send it as is.
+    let mut v1 = event("apigw_v1");
+    v1["path"] = json!("/files/100% done/a%2Fb");
+    let (req, _) = lambda::into_request(v1).unwrap();
+    assert_eq!(req.uri().path(), "/files/100%25%20done/a%252Fb");
+
+    let mut alb = event("alb");
+    alb["path"] = json!("/files/a%2Fb");
+    let (req, _) = lambda::into_request(alb).unwrap();
+    assert_eq!(req.uri().path(), "/files/a%2Fb");
+
+    assert!(lambda::into_request(json!({ "foo": "bar" })).is_err());
+}
+
+#[tokio::test]
+async fn can_handle_api_gateway_v1_event() {
+    let res = handler().handle(event("apigw_v1")).await.unwrap();
+
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["isBase64Encoded"], false);
+    assert!(res["body"].as_str().unwrap().starts_with("POST /users/alice?"));
+    assert!(res["body"].as_str().unwrap().ends_with(" from 192.0.2.1"));
+    assert_eq!(res["multiValueHeaders"]["x-body"], json!(["{\"name\":\"alice\"}"]));
+    assert_eq!(res["multiValueHeaders"]["set-cookie"], json!(["a=1", "b=2"]));
+}
+
+#[tokio::test]
+async fn can_handle_api_gateway_v2_event() {
+    let res = handler().handle(event("apigw_v2")).await.unwrap();
+
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["body"], "POST /users/bob?tag=a&tag=b from 2001:db8::1");
+    assert_eq!(res["headers"]["x-body"], "hello");
+    assert_eq!(res["headers"]["x-cookie"], "session=abc; theme=dark");
+    assert!(res["headers"].get("set-cookie").is_none());
+    assert_eq!(res["cookies"], json!(["a=1", "b=2"]));
+}
+
+#[tokio::test]
+async fn can_handle_alb_event() {
+    let res = handler().handle(event("alb")).await.unwrap();
+
+    assert_eq!(res["statusCode"], 200);
+    assert_eq!(res["statusDescription"], "200 OK");
+    assert_eq!(res["body"], "GET /users/carol?q=a%20b from 203.0.113.9");
+    assert_eq!(res["headers"]["set-cookie"], "b=2");
+
+    let mut multi_value_event = event("alb");
+    multi_value_event["multiValueHeaders"] = json!({ "host": ["example.com"] });
+    let res = handler().handle(multi_value_event).await.unwrap();
+    assert_eq!(res["multiValueHeaders"]["set-cookie"], json!(["a=1", "b=2"]));
+    assert_eq!(res["body"], "GET /users/carol?q=a%20b from ");
+}
+
+#[tokio::test]
+async fn can_encode_binary_response_body() {
+    let res = lambda::from_response(
+        Response::new(Body::from(vec![0xff, 0x00, 0x10])),
+        EventKind::ApiGatewayV2,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(res["isBase64Encoded"], true);
+    assert_eq!(res["body"], "/wAQ");
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "multipart")]
+
+use self::support::{into_text, serve};
+use hyper::{Body, Client, StatusCode};
+use routerify::extract::{handler, FieldData, Multipart, MultipartConfig, MultipartError, Rejection};
+use routerify::{RouteError, Router};
+use std::error::Error;
+
+mod support;
+
+const BOUNDARY: &str = "X-ROUTERIFY-BOUNDARY";
+
+async fn upload(mut multipart: Multipart) -> Result<String, RouteError> {
+    let mut lines = Vec::new();
+
+    while let Some(field) = multipart.next_field().await.map_err(Rejection::from)? {
+        let name = field.name().unwrap_or_default().to_owned();
+        let file_name = field.file_name().map(ToOwned::to_owned);
+
+        match field.data().await.map_err(Rejection::from)? {
+            FieldData::Memory(bytes) => lines.push(format!("{}={}", name, String::from_utf8_lossy(&bytes))),
+            FieldData::File(file) => {
+                let text = tokio::fs::read_to_string(file.path()).await?;
+                lines.push(format!("{}@{:?}={} ({} bytes)", name, file_name, text, file.len()));
+            }
+        }
+    }
+
+    Ok(lines.join("\n"))
+}
+
+fn router(config: MultipartConfig) -> Router<Body, RouteError> {
+    Router::builder()
+        .data(config)
+        .post("/upload", handler(upload))
+        .build()
+        .unwrap()
+}
+
+fn body(fields: &[(&str, Option<&str>, &str)]) -> String {
+    let mut body = String::new();
+    for (name, file_name, content) in fields {
+        body.push_str(&format!("--{}\r\n", BOUNDARY));
+        match file_name {
+            Some(file_name) => body.push_str(&format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: text/plain\r\n\r\n",
+                name, file_name
+            )),
+            None => body.push_str(&format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name)),
+        }
+        body.push_str(content);
+        body.push_str("\r\n");
+    }
+    body.push_str(&format!("--{}--\r\n", BOUNDARY));
+    body
+}
+
+#[tokio::test]
+async fn can_stream_fields_and_spill_files() {
+    let serve = serve(router(MultipartConfig::new().memory_threshold(8))).await;
+
+    let req = serve
+        .new_request("POST", "/upload")
+        .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+        .body(Body::from(body(&[
+            ("title", None, "Holiday"),
+            ("small", Some("a.txt"), "tiny"),
+            ("photo", Some("b.txt"), "a file larger than the threshold"),
+        ])))
+        .unwrap();
+    let resp = Client::new().request(req).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "title=Holiday\nsmall=tiny\nphoto@Some(\"b.txt\")=a file larger than the threshold (32 bytes)"
+    );
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_multipart_requests() {
+    let serve = serve(router(MultipartConfig::new().total_limit(256).field_limit(16))).await;
+    let client = Client::new();
+
+    let cases = vec![
+        (
+            body(&[("title", None, "a title longer than 16 bytes")]),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        (
+            body(&[("title", None, &"x".repeat(300))]),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        (format!("--{}\r\nbroken", BOUNDARY), StatusCode::BAD_REQUEST),
+    ];
+
+    for (body, status) in cases {
+        let req = serve
+            .new_request("POST", "/upload")
+            .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+            .body(Body::from(body))
+            .unwrap();
+        let resp = client.request(req).await.unwrap();
+        assert_eq!(resp.status(), status);
+    }
+
+    let req = serve
+        .new_request("POST", "/upload")
+        .header("content-type", "text/plain")
+        .body(Body::from("title=Holiday"))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_handle_multipart_errors_in_error_handler() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .data(MultipartConfig::new().field_limit(4))
+        .post("/upload", handler(upload))
+        .err_handler(|err: RouteError| async move {
+            let source = err.downcast_ref::<Rejection>().and_then(|rejection| rejection.source());
+            let text = match source.and_then(|source| source.downcast_ref::<MultipartError>()) {
+                Some(MultipartError::FieldTooLarge { field_name, limit }) => format!("{:?} {}", field_name, limit),
+                _ => "unexpected".to_owned(),
+            };
+            hyper::Response::builder()
+                .status(StatusCode::PAYLOAD_TOO_LARGE)
+                .body(Body::from(text))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let req = serve
+        .new_request("POST", "/upload")
+        .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+        .body(Body::from(body(&[("title", None, "Holiday")])))
+        .unwrap();
+    let resp = Client::new().request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Some(\"title\") 4");
+
+    serve.shutdown();
+}
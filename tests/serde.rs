@@ -0,0 +1,144 @@
+#![cfg(feature = "serde")]
+
+use self::support::{into_text, serve};
+use hyper::{Body, Client, StatusCode};
+use routerify::extract::{handler, BodyLimit, Form, Json, Query};
+use routerify::{RouteError, Router};
+use serde::{Deserialize, Serialize};
+
+mod support;
+
+#[derive(Deserialize, Serialize)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    page: u32,
+    per_page: Option<u32>,
+}
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .data(BodyLimit(64))
+        .post("/json", handler(|Json(user): Json<User>| async move { Ok(Json(user)) }))
+        .post(
+            "/form",
+            handler(|Form(user): Form<User>| async move { Ok(format!("{} {}", user.id, user.name)) }),
+        )
+        .get(
+            "/query",
+            handler(|Query(page): Query<Pagination>| async move { Ok(format!("{} {:?}", page.page, page.per_page)) }),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn can_extract_and_respond_with_json() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    let req = serve
+        .new_request("POST", "/json")
+        .header("content-type", "application/json; charset=utf-8")
+        .body(Body::from(r#"{"id":7,"name":"alice"}"#))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert_eq!(into_text(resp.into_body()).await, r#"{"id":7,"name":"alice"}"#);
+
+    let cases = vec![
+        (
+            "text/plain",
+            r#"{"id":7,"name":"alice"}"#,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+        ("application/json", r#"{"id":7,"#, StatusCode::BAD_REQUEST),
+        (
+            "application/json",
+            r#"{"id":"seven"}"#,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            "application/vnd.api+json",
+            r#"{"id":7}"#,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            "application/json",
+            r#"{"id":7,"name":"a name which is longer than the body limit of the router"}"#,
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+    ];
+
+    for (content_type, body, status) in cases {
+        let req = serve
+            .new_request("POST", "/json")
+            .header("content-type", content_type)
+            .body(Body::from(body))
+            .unwrap();
+        let resp = client.request(req).await.unwrap();
+        assert_eq!(resp.status(), status, "{} {}", content_type, body);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_extract_forms() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    let req = serve
+        .new_request("POST", "/form")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from("id=7&name=alice+smith"))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "7 alice smith");
+
+    let req = serve
+        .new_request("POST", "/form")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from("id=seven"))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = serve
+        .new_request("POST", "/form")
+        .body(Body::from("id=7&name=alice"))
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_extract_query_strings() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    let req = serve.new_request("GET", "/query?page=2").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "2 None");
+
+    let req = serve
+        .new_request("GET", "/query?page=2&per_page=50")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "2 Some(50)");
+
+    let req = serve.new_request("GET", "/query?page=two").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    serve.shutdown();
+}
@@ -0,0 +1,145 @@
+use hyper::{Body, Response};
+use routerify::{Listener, Router, Server};
+use std::convert::Infallible;
+use std::io;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+fn router() -> Router<Body, Infallible> {
+    Router::builder()
+        .get("/", || async move { Ok(Response::new(Body::from("Home page"))) })
+        .get("/slow", || async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(Response::new(Body::from("done")))
+        })
+        .build()
+        .unwrap()
+}
+
+async fn request<IO: AsyncRead + AsyncWrite + Unpin>(mut io: IO, path: &str) -> String {
+    io.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).as_bytes())
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    io.read_to_string(&mut resp).await.unwrap();
+    resp
+}
+
+#[tokio::test]
+async fn can_serve_tcp_listener_until_shutdown_signal() {
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let server = Server::new(router()).unwrap().shutdown_signal(async move {
+        rx.await.unwrap();
+    });
+    let shutdown = server.shutdown();
+    let server = tokio::spawn(server.serve(listener));
+
+    let resp = request(TcpStream::connect(addr).await.unwrap(), "/").await;
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.ends_with("\r\n\r\nHome page"));
+
+    tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(shutdown.is_triggered());
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn can_serve_unix_listener() {
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = std::env::temp_dir().join(format!("routerify-server-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let server = Server::new(router()).unwrap();
+    let shutdown = server.shutdown();
+    let server = tokio::spawn(server.serve(listener));
+
+    let resp = request(UnixStream::connect(&path).await.unwrap(), "/").await;
+    assert!(resp.ends_with("\r\n\r\nHome page"));
+
+    shutdown.trigger();
+    server.await.unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn can_stop_after_drain_timeout() {
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = Server::new(router()).unwrap().drain_timeout(Duration::from_millis(100));
+    let shutdown = server.shutdown();
+    let server = tokio::spawn(server.serve(listener));
+
+    let slow = tokio::spawn(async move { request(TcpStream::connect(addr).await.unwrap(), "/slow").await });
+    while shutdown.in_flight() == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    shutdown.trigger();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    // The connection of the slow request is closed without a response.
+    assert_eq!(slow.await.unwrap(), "");
+}
+
+// Fails to accept the first connection like a process running out of file descriptors.
+struct FailingListener {
+    listener: tokio::net::TcpListener,
+    failed: bool,
+}
+
+impl Listener for FailingListener {
+    type Io = tokio::net::TcpStream;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Io>> {
+        if !self.failed {
+            self.failed = true;
+            return Poll::Ready(Err(io::Error::other("Too many open files")));
+        }
+
+        self.listener.poll_accept(cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+#[tokio::test]
+async fn keeps_serving_after_accept_errors() {
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = Server::new(router()).unwrap();
+    let shutdown = server.shutdown();
+    let server = tokio::spawn(server.serve(FailingListener {
+        listener,
+        failed: false,
+    }));
+
+    let resp = tokio::time::timeout(
+        Duration::from_secs(5),
+        request(TcpStream::connect(addr).await.unwrap(), "/"),
+    )
+    .await
+    .unwrap();
+    assert!(resp.ends_with("\r\n\r\nHome page"));
+
+    shutdown.trigger();
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,140 @@
+use hyper::body::HttpBody;
+use hyper::{service::Service, Body, Request, Response, StatusCode};
+use routerify::prelude::*;
+use routerify::{Middleware, RequestServiceBuilder, Router};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+fn router(release: Arc<Notify>, reject_during_shutdown: bool) -> Router<Body, Infallible> {
+    Router::builder()
+        .reject_during_shutdown(reject_during_shutdown)
+        .middleware(Middleware::post(|mut res| async move {
+            res.headers_mut().insert("x-post-middleware", "1".parse().unwrap());
+            Ok(res)
+        }))
+        .get("/slow", move || {
+            let release = release.clone();
+            async move {
+                release.notified().await;
+                Ok(Response::new(Body::from("done")))
+            }
+        })
+        .get("/stream", |req: Request<Body>| async move {
+            let shutdown = req.shutdown().unwrap().clone();
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                sender.send_data("first chunk".into()).await.unwrap();
+                shutdown.triggered().await;
+                sender.send_data(", last chunk".into()).await.unwrap();
+            });
+            Ok(Response::new(body))
+        })
+        .get("/watch", |req: Request<Body>| async move {
+            let shutdown = req.shutdown().unwrap().clone();
+            shutdown.triggered().await;
+            Ok(Response::new(Body::from("shutting down")))
+        })
+        .build()
+        .unwrap()
+}
+
+fn request(path: &str) -> Request<Body> {
+    Request::builder().uri(path).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn can_drain_in_flight_requests() {
+    let release = Arc::new(Notify::new());
+    let builder = RequestServiceBuilder::new(router(release.clone(), true)).unwrap();
+    let shutdown = builder.shutdown();
+    let mut service = builder.build("127.0.0.1:4711".parse::<std::net::SocketAddr>().unwrap());
+
+    let in_flight = tokio::spawn(service.call(request("/slow")));
+    tokio::task::yield_now().await;
+    assert_eq!(shutdown.in_flight(), 1);
+    assert!(!shutdown.is_triggered());
+
+    shutdown.trigger();
+    assert!(shutdown.is_triggered());
+
+    let res = service.call(request("/slow")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()["connection"], "close");
+    // The rejected requests still go through the post middlewares.
+    assert_eq!(res.headers()["x-post-middleware"], "1");
+    assert_eq!(shutdown.in_flight(), 1);
+
+    let drain = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { shutdown.drain(Duration::from_secs(5)).await }
+    });
+    tokio::task::yield_now().await;
+    release.notify_one();
+
+    let res = in_flight.await.unwrap().unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(hyper::body::to_bytes(res.into_body()).await.unwrap(), "done");
+    assert!(drain.await.unwrap());
+    assert_eq!(shutdown.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn can_give_up_draining_after_deadline() {
+    let release = Arc::new(Notify::new());
+    let builder = RequestServiceBuilder::new(router(release, false)).unwrap();
+    let shutdown = builder.shutdown();
+    let mut service = builder.build("127.0.0.1:4711".parse::<std::net::SocketAddr>().unwrap());
+
+    let _in_flight = tokio::spawn(service.call(request("/slow")));
+    tokio::task::yield_now().await;
+
+    shutdown.trigger();
+    assert!(!shutdown.drain(Duration::from_millis(50)).await);
+    assert_eq!(shutdown.in_flight(), 1);
+
+    // New requests are still processed as the router doesn't reject them.
+    let res = service.call(request("/watch")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn can_observe_shutdown_from_handlers() {
+    let builder = RequestServiceBuilder::new(router(Arc::new(Notify::new()), false)).unwrap();
+    let shutdown = builder.shutdown();
+    let mut service = builder.build("127.0.0.1:4711".parse::<std::net::SocketAddr>().unwrap());
+
+    let watching = tokio::spawn(service.call(request("/watch")));
+    tokio::task::yield_now().await;
+    shutdown.trigger();
+
+    let res = watching.await.unwrap().unwrap();
+    assert_eq!(hyper::body::to_bytes(res.into_body()).await.unwrap(), "shutting down");
+}
+
+#[tokio::test]
+async fn can_drain_streaming_responses() {
+    let builder = RequestServiceBuilder::new(router(Arc::new(Notify::new()), false)).unwrap();
+    let shutdown = builder.shutdown();
+    let mut service = builder.build("127.0.0.1:4711".parse::<std::net::SocketAddr>().unwrap());
+
+    // The request stays in flight while the body is being streamed.
+    let mut body = service.call(request("/stream")).await.unwrap().into_body();
+    assert_eq!(body.data().await.unwrap().unwrap(), "first chunk");
+    assert_eq!(shutdown.in_flight(), 1);
+
+    shutdown.trigger();
+    assert!(!shutdown.drain(Duration::from_millis(50)).await);
+
+    assert_eq!(body.data().await.unwrap().unwrap(), ", last chunk");
+    assert!(body.data().await.is_none());
+    assert!(shutdown.drain(Duration::from_secs(5)).await);
+    assert_eq!(shutdown.in_flight(), 0);
+
+    // A dropped body isn't in flight anymore.
+    let body = service.call(request("/stream")).await.unwrap().into_body();
+    assert_eq!(shutdown.in_flight(), 1);
+    drop(body);
+    assert_eq!(shutdown.in_flight(), 0);
+}
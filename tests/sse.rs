@@ -0,0 +1,62 @@
+#![cfg(feature = "sse")]
+
+use self::support::{into_text, serve};
+use futures::stream::{self, StreamExt};
+use hyper::{Body, Client, StatusCode};
+use routerify::extract::handler;
+use routerify::sse::{Event, LastEventId, Sse};
+use routerify::{RouteError, Router};
+use std::convert::Infallible;
+use std::time::Duration;
+
+mod support;
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .get(
+            "/events",
+            handler(|LastEventId(last_id): LastEventId| async move {
+                let first = Event::new()
+                    .event("resume")
+                    .data(last_id.unwrap_or_else(|| "none".to_owned()));
+                let events = stream::iter(vec![first, Event::new().id("1").data("line 1\nline 2")]).chain(
+                    // The last event comes after a few keep-alive intervals.
+                    stream::once(async {
+                        tokio::time::sleep(Duration::from_millis(120)).await;
+                        Event::new().id("2").data("late")
+                    }),
+                );
+
+                Ok(Sse::new(events.map(Ok::<_, Infallible>)).keep_alive(Some(Duration::from_millis(50))))
+            }),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn can_stream_server_sent_events() {
+    let serve = serve(router()).await;
+
+    let req = serve
+        .new_request("GET", "/events")
+        .header("last-event-id", "41")
+        .body(Body::empty())
+        .unwrap();
+    let resp = Client::new().request(req).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    assert_eq!(resp.headers()["cache-control"], "no-cache");
+
+    let text = into_text(resp.into_body()).await;
+
+    assert!(
+        text.starts_with("event: resume\ndata: 41\n\ndata: line 1\ndata: line 2\nid: 1\n\n:\n\n"),
+        "{:?}",
+        text
+    );
+    assert!(text.ends_with(":\n\ndata: late\nid: 2\n\n"), "{:?}", text);
+
+    serve.shutdown();
+}
@@ -0,0 +1,244 @@
+#![cfg(feature = "fs")]
+
+use self::support::{into_text, serve};
+use hyper::{Body, Client, Response, StatusCode};
+use routerify::fs::StaticFiles;
+use routerify::{RouteError, Router};
+use std::io::Read;
+
+mod support;
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/", || async move { Ok(Response::new(Body::from("Home"))) })
+        .scope(
+            "/static",
+            StaticFiles::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/static"))
+                .index_file("index.html")
+                .precompressed_gzip(true)
+                .router(),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn can_serve_static_files() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    assert_eq!(resp.headers()["content-length"], "14");
+    assert_eq!(resp.headers()["accept-ranges"], "bytes");
+    assert_eq!(resp.headers()["vary"], "accept-encoding");
+    assert!(resp.headers().contains_key("last-modified"));
+    let etag = resp.headers()["etag"].clone();
+    assert_eq!(into_text(resp.into_body()).await, "Hello, World!\n");
+
+    // Conditional GET.
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .header("if-none-match", etag.clone())
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()["etag"], etag);
+
+    // Range requests.
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .header("range", "bytes=7-11")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(resp.headers()["content-range"], "bytes 7-11/14");
+    assert_eq!(into_text(resp.into_body()).await, "World");
+
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .header("range", "bytes=20-")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(resp.headers()["content-range"], "bytes */14");
+
+    // A stale `If-Range` validator gets the whole file.
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .header("range", "bytes=7-11")
+        .header("if-range", "\"stale\"")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "Hello, World!\n");
+
+    // Precompressed variant.
+    let req = serve
+        .new_request("GET", "/static/hello.txt")
+        .header("accept-encoding", "br, gzip")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-encoding"], "gzip");
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    let encoded = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&encoded[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, "Hello, World!\n");
+
+    let req = serve
+        .new_request("HEAD", "/static/docs/style.css")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/css; charset=utf-8");
+    assert_eq!(resp.headers()["content-length"], "21");
+    assert_eq!(into_text(resp.into_body()).await, "");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_serve_directory_index_files() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    let req = serve.new_request("GET", "/static/").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/html; charset=utf-8");
+    assert_eq!(into_text(resp.into_body()).await, "<h1>Home</h1>\n");
+
+    let req = serve
+        .new_request("GET", "/static/docs?v=1")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(resp.headers()["location"], "/static/docs/?v=1");
+
+    let req = serve.new_request("GET", "/static/docs/").body(Body::empty()).unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "<h1>Docs</h1>\n");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn refuses_to_escape_the_root_directory() {
+    let serve = serve(router()).await;
+    let client = Client::new();
+
+    for path in [
+        "/static/missing.txt",
+        "/static/hello.txt/",
+        "/static/../lambda/alb.json",
+        "/static/%2e%2e/lambda/alb.json",
+        "/static/docs/..%2f..%2flambda%2falb.json",
+        "/static/..%5clambda%5calb.json",
+    ] {
+        let req = serve.new_request("GET", path).body(Body::empty()).unwrap();
+        let resp = client.request(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", path);
+    }
+
+    let req = serve
+        .new_request("POST", "/static/hello.txt")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_fall_back_to_the_index_file_of_an_spa() {
+    let api = Router::builder()
+        .get("/users", || async move { Ok(Response::new(Body::from("[]"))) })
+        .build()
+        .unwrap();
+
+    let router: Router<Body, RouteError> = Router::builder()
+        .scope("/app/api", api)
+        .scope(
+            "/app",
+            StaticFiles::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/static"))
+                .index_file("index.html")
+                .fallback_file("index.html")
+                .router(),
+        )
+        .any(|| async move {
+            Ok((
+                StatusCode::NOT_FOUND,
+                [("content-type", "application/json")],
+                r#"{"error":"Not Found"}"#,
+            ))
+        })
+        .build()
+        .unwrap();
+
+    let serve = serve(router).await;
+    let client = Client::new();
+
+    for path in ["/app/", "/app/settings/profile", "/app/docs/missing"] {
+        let req = serve
+            .new_request("GET", path)
+            .header("accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+            .body(Body::empty())
+            .unwrap();
+        let resp = client.request(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK, "{}", path);
+        assert_eq!(resp.headers()["content-type"], "text/html; charset=utf-8");
+        assert_eq!(into_text(resp.into_body()).await, "<h1>Home</h1>\n");
+    }
+
+    // The real assets are served as usual.
+    let req = serve
+        .new_request("GET", "/app/hello.txt")
+        .header("accept", "text/html")
+        .body(Body::empty())
+        .unwrap();
+    let resp = client.request(req).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Hello, World!\n");
+
+    // The misses which aren't page navigations get the 404 of the root router, e.g. a missing asset or API route.
+    for (method, path, accept) in [
+        ("GET", "/app/missing.js", "*/*"),
+        ("GET", "/app/api/missing", "application/json"),
+        ("HEAD", "/app/settings/profile", "text/html"),
+        ("POST", "/app/settings", "text/html"),
+    ] {
+        let req = serve
+            .new_request(method, path)
+            .header("accept", accept)
+            .body(Body::empty())
+            .unwrap();
+        let resp = client.request(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{} {}", method, path);
+        assert_eq!(
+            resp.headers()["content-type"],
+            "application/json",
+            "{} {}",
+            method,
+            path
+        );
+    }
+
+    serve.shutdown();
+}
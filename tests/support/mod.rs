@@ -1,5 +1,5 @@
 use hyper::{
-    body::{self, Body, HttpBody},
+    body::{self, HttpBody},
     Server,
 };
 use routerify::{Router, RouterService};
@@ -51,6 +51,9 @@ where
     Serve { addr, tx }
 }
 
-pub async fn into_text(body: Body) -> String {
+pub async fn into_text<B: HttpBody>(body: B) -> String
+where
+    B::Error: std::fmt::Debug,
+{
     String::from_utf8_lossy(&body::to_bytes(body).await.unwrap()).to_string()
 }
@@ -0,0 +1,81 @@
+#![cfg(feature = "test-util")]
+
+use hyper::{Body, Request, Response, StatusCode};
+use routerify::prelude::*;
+use routerify::test::TestClient;
+use routerify::{RouteError, Router};
+use serde_json::{json, Value};
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/ip", |req: Request<Body>| async move {
+            Ok(Response::new(Body::from(req.remote_addr().to_string())))
+        })
+        .post("/echo", |req: Request<Body>| async move {
+            let content_type = req.headers()["content-type"].clone();
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+            Ok(Response::builder()
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap())
+        })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn can_send_requests_without_sockets() {
+    let client = TestClient::new(router()).unwrap();
+
+    let res = client.get("/ip").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "unknown");
+
+    let res = client.get("/missing").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.header("content-type"), Some("text/plain"));
+    assert_eq!(res.bytes().await.unwrap(), "Not Found");
+
+    let req = Request::builder()
+        .method("OPTIONS")
+        .uri("/ip")
+        .body(Body::empty())
+        .unwrap();
+    let res = client.send(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn can_fake_remote_addr() {
+    let client = TestClient::new(router())
+        .unwrap()
+        .remote_addr("192.0.2.1:4711".parse::<std::net::SocketAddr>().unwrap());
+
+    let res = client.get("/ip").send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "192.0.2.1:4711");
+}
+
+#[tokio::test]
+async fn can_send_and_read_json() {
+    let client = TestClient::new(router()).unwrap();
+
+    let res = client
+        .post("/echo")
+        .json(&json!({ "name": "alice", "age": 30 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.header("content-type"), Some("application/json"));
+
+    let value: Value = res.json().await.unwrap();
+    assert_eq!(value, json!({ "name": "alice", "age": 30 }));
+
+    let res = client
+        .post("/echo")
+        .header("content-type", "text/plain")
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+}
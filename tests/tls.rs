@@ -0,0 +1,104 @@
+#![cfg(feature = "rustls")]
+
+use hyper::server::conn::Http;
+use hyper::service::Service;
+use hyper::{Body, Request, Response};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use routerify::prelude::*;
+use routerify::{RouteError, Router, RouterService};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+#[tokio::test]
+async fn can_access_tls_connection_info() {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+    let server_key = KeyPair::generate().unwrap();
+    let server_cert = CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .signed_by(&server_key, &ca_cert, &ca_key)
+        .unwrap();
+    let client_key = KeyPair::generate().unwrap();
+    let client_cert = CertificateParams::new(vec!["client".to_string()])
+        .unwrap()
+        .signed_by(&client_key, &ca_cert, &ca_key)
+        .unwrap();
+
+    let mut roots = RootCertStore::empty();
+    roots.add(ca_cert.der().clone()).unwrap();
+    let roots = Arc::new(roots);
+
+    let mut server_config = ServerConfig::builder()
+        .with_client_cert_verifier(WebPkiClientVerifier::builder(roots.clone()).build().unwrap())
+        .with_single_cert(
+            vec![server_cert.der().clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(server_key.serialize_der())),
+        )
+        .unwrap();
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let mut client_config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_client_auth_cert(
+            vec![client_cert.der().clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(client_key.serialize_der())),
+        )
+        .unwrap();
+    client_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    let expected_cert = client_cert.der().to_vec();
+    let router: Router<Body, RouteError> = Router::builder()
+        .get("/", move |req: Request<Body>| {
+            let expected_cert = expected_cert.clone();
+            async move {
+                let info = req.connection_info();
+                let text = format!(
+                    "{} {} {} {}",
+                    String::from_utf8_lossy(info.alpn_protocol().unwrap()),
+                    info.server_name().unwrap(),
+                    info.peer_certificates().unwrap()[0] == expected_cert,
+                    info.local_addr().as_tcp().unwrap().ip(),
+                );
+                Ok(Response::new(Body::from(text)))
+            }
+        })
+        .build()
+        .unwrap();
+    let mut router_service = RouterService::new(router).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(server_config));
+    let connector = TlsConnector::from(Arc::new(client_config));
+
+    let (_, resp) = tokio::join!(
+        async {
+            let (stream, _) = listener.accept().await.unwrap();
+            let stream = acceptor.accept(stream).await.unwrap();
+            let service = router_service.call(&stream).await.unwrap();
+            Http::new().serve_connection(stream, service).await.unwrap();
+        },
+        async {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            let mut stream = connector.connect(server_name, stream).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut resp = String::new();
+            let _ = stream.read_to_string(&mut resp).await;
+            resp
+        }
+    );
+
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.ends_with("\r\n\r\nhttp/1.1 localhost true 127.0.0.1"));
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "tower")]
+
+use self::support::into_text;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use routerify::{InFlightBody, RouteError, Router, RouterService};
+use std::future::{ready, Ready};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::limit::ConcurrencyLimitLayer;
+use tower::{service_fn, timeout::TimeoutLayer, Service, ServiceBuilder, ServiceExt};
+
+#[allow(dead_code)]
+mod support;
+
+#[tokio::test]
+async fn can_use_router_service_as_tower_service() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .get("/", || async move { Ok(Response::new(Body::from("Hello world"))) })
+        .build()
+        .unwrap();
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_secs(1))
+        .service(RouterService::new(router).unwrap());
+
+    let resp = service
+        .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "Hello world");
+}
+
+#[tokio::test]
+async fn can_wrap_scoped_routes_with_layer() {
+    let slow: Router<Body, RouteError> = Router::builder()
+        .get("/", || async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(Response::new(Body::from("Too late")))
+        })
+        .layer(TimeoutLayer::new(Duration::from_millis(10)))
+        .build()
+        .unwrap();
+    let router: Router<Body, RouteError> = Router::builder()
+        .get("/fast", || async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(Response::new(Body::from("Fast enough")))
+        })
+        .scope("/slow", slow)
+        .build()
+        .unwrap();
+    let mut service = RouterService::new(router).unwrap();
+
+    let resp = call(&mut service, "/slow").await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let resp = call(&mut service, "/fast").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "Fast enough");
+}
+
+#[tokio::test]
+async fn can_route_to_tower_service() {
+    let metrics = service_fn(|req: Request<Body>| async move {
+        Ok::<_, RouteError>(Response::new(Body::from(format!("metrics at {}", req.uri().path()))))
+    });
+    let router: Router<Body, RouteError> = Router::builder()
+        .route_service("/metrics/*", vec![Method::GET], metrics)
+        .build()
+        .unwrap();
+    let mut service = RouterService::new(router).unwrap();
+
+    let resp = call(&mut service, "/metrics/cpu").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "metrics at /metrics/cpu");
+
+    let resp = call(&mut service, "/other").await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn can_share_layered_service_between_requests() {
+    let router: Router<Body, RouteError> = Router::builder()
+        .get("/", || async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(Response::new(Body::from("Done")))
+        })
+        .layer(ConcurrencyLimitLayer::new(1))
+        .build()
+        .unwrap();
+    let mut service = RouterService::new(router).unwrap();
+
+    let mut calls = Vec::new();
+    for _ in 0..2 {
+        let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let service = ServiceExt::<Request<Body>>::ready(&mut service).await.unwrap();
+        calls.push(service.call(req));
+    }
+
+    // The requests wait for each other as they're limited by the same service.
+    let start = Instant::now();
+    let second = calls.pop().unwrap();
+    let first = calls.pop().unwrap();
+    let (first, second) = tokio::join!(first, second);
+    assert_eq!(first.unwrap().status(), StatusCode::OK);
+    assert_eq!(second.unwrap().status(), StatusCode::OK);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn can_route_to_stateful_tower_service() {
+    struct Counter(u32);
+
+    impl Service<Request<Body>> for Counter {
+        type Response = Response<Body>;
+        type Error = RouteError;
+        type Future = Ready<Result<Response<Body>, RouteError>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), RouteError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<Body>) -> Self::Future {
+            self.0 += 1;
+            ready(Ok(Response::new(Body::from(self.0.to_string()))))
+        }
+    }
+
+    let router: Router<Body, RouteError> = Router::builder()
+        .route_service("/count", vec![Method::GET], Counter(0))
+        .build()
+        .unwrap();
+    let mut service = RouterService::new(router).unwrap();
+
+    for count in 1..=3 {
+        let resp = call(&mut service, "/count").await;
+        assert_eq!(into_text(resp.into_body()).await, count.to_string());
+    }
+}
+
+async fn call(service: &mut RouterService<Body, RouteError>, uri: &str) -> Response<InFlightBody<Body>> {
+    let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    ServiceExt::<Request<Body>>::ready(service)
+        .await
+        .unwrap()
+        .call(req)
+        .await
+        .unwrap()
+}
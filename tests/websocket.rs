@@ -0,0 +1,95 @@
+#![cfg(feature = "websocket")]
+
+use futures::{SinkExt, StreamExt};
+use hyper::server::conn::Http;
+use hyper::service::Service;
+use hyper::{Body, Response};
+use routerify::extract::handler;
+use routerify::websocket::{Message, WebSocketUpgrade};
+use routerify::{Middleware, RouteError, Router, RouterService};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::WebSocketStream;
+
+fn router() -> Router<Body, RouteError> {
+    Router::builder()
+        .middleware(Middleware::post(|mut res: Response<Body>| async move {
+            res.headers_mut().insert("x-post", "1".parse().unwrap());
+            Ok(res)
+        }))
+        .get(
+            "/echo",
+            handler(|ws: WebSocketUpgrade| async move {
+                Ok(ws.protocols(["chat"]).on_upgrade(|mut socket| async move {
+                    let protocol = socket.protocol().unwrap_or_default().to_owned();
+                    while let Some(Ok(msg)) = socket.recv().await {
+                        if let Message::Text(text) = msg {
+                            let reply = format!("{}: {}", protocol, text.as_str());
+                            socket.send(Message::text(reply)).await.unwrap();
+                        }
+                    }
+                }))
+            }),
+        )
+        .build()
+        .unwrap()
+}
+
+async fn connect(handshake: &str) -> (DuplexStream, String) {
+    let mut router_service = RouterService::new(router()).unwrap();
+    let (mut client, server) = tokio::io::duplex(4096);
+    let service = router_service.call(&server).await.unwrap();
+    tokio::spawn(Http::new().serve_connection(server, service).with_upgrades());
+
+    client.write_all(handshake.as_bytes()).await.unwrap();
+
+    // Reads the response head byte by byte, so none of the WebSocket frames are consumed.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0; 1];
+        if client.read(&mut byte).await.unwrap() == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+
+    (client, String::from_utf8(head).unwrap())
+}
+
+#[tokio::test]
+async fn can_upgrade_to_websocket() {
+    let (client, head) = connect(
+        "GET /echo HTTP/1.1\r\nHost: localhost\r\nConnection: keep-alive, Upgrade\r\nUpgrade: websocket\r\n\
+         Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+         Sec-WebSocket-Protocol: json, chat\r\n\r\n",
+    )
+    .await;
+
+    assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"), "{}", head);
+    assert!(head.contains("sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    assert!(head.contains("sec-websocket-protocol: chat\r\n"));
+    assert!(head.contains("x-post: 1\r\n"));
+
+    let mut socket = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+    socket.send(Message::text("hello")).await.unwrap();
+
+    match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => assert_eq!(text.as_str(), "chat: hello"),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    socket.close(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn can_reject_invalid_upgrades() {
+    let (_, head) = connect("GET /echo HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await;
+    assert!(head.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", head);
+
+    let (_, head) = connect(
+        "GET /echo HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+         Sec-WebSocket-Version: 8\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+    )
+    .await;
+    assert!(head.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", head);
+}